@@ -0,0 +1,103 @@
+/// Run of consecutive non-blank lines from an input split by blank lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// Position of the block in the input, starting at 0
+    index: usize,
+    /// Line number of the first line of the block in the input, starting at 1
+    first_line: usize,
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Block<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn first_line(&self) -> usize {
+        self.first_line
+    }
+
+    /// Lines without their line endings or trailing whitespace
+    pub fn lines(&self) -> &[&'a str] {
+        &self.lines
+    }
+
+    /// Lines together with their line number in the input
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        (self.first_line..).zip(self.lines.iter().copied())
+    }
+
+    /// Lines joined by `\n`
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Length shared by every line, or an error pointing at the first line
+    /// with a different length
+    pub fn width(&self) -> Result<usize, String> {
+        let width = self.lines.first().map_or(0, |line| line.len());
+        match self.numbered_lines().find(|(_, line)| line.len() != width) {
+            Some((line_number, line)) => Err(self.error(
+                line_number,
+                &format!("Expected {width} columns, found {}.", line.len()),
+            )),
+            None => Ok(width),
+        }
+    }
+
+    /// Prefixes `message` with the block and line it refers to
+    pub fn error(&self, line_number: usize, message: &str) -> String {
+        format!("Block {}, line {line_number}: {message}", self.index + 1)
+    }
+}
+
+/// Splits `input` on blank lines
+///
+/// Lines may end in `\n` or `\r\n`, trailing whitespace is dropped, and any
+/// number of blank lines, including ones made only of whitespace, separate two
+/// blocks.
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks: Vec<Block> = vec![];
+    let mut in_block = false;
+    for (line_number, line) in (1..).zip(input.lines()) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            in_block = false;
+        } else if in_block {
+            if let Some(block) = blocks.last_mut() {
+                block.lines.push(line);
+            }
+        } else {
+            in_block = true;
+            blocks.push(Block {
+                index: blocks.len(),
+                first_line: line_number,
+                lines: vec![line],
+            });
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::blocks;
+
+    #[test]
+    fn blocks_test() {
+        let input = "\r\n#.#\r\n.#.  \r\n\r\n \t\r\n\n##\n.\n\n";
+        let blocks = blocks(input);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines(), &["#.#", ".#."]);
+        assert_eq!(blocks[0].text(), "#.#\n.#.");
+        assert_eq!(blocks[0].width(), Ok(3));
+        assert_eq!(
+            blocks[1].numbered_lines().collect::<Vec<_>>(),
+            vec![(7, "##"), (8, ".")]
+        );
+        assert_eq!(
+            blocks[1].width(),
+            Err("Block 2, line 8: Expected 2 columns, found 1.".to_owned())
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+/// Shape of a sequence of states that eventually repeats
+///
+/// The states at indices `prefix..(prefix + period)` repeat forever, and none
+/// of the states before them is ever seen again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Smallest index that holds the same state as `index`
+    pub fn reduce(&self, index: usize) -> usize {
+        if index < self.prefix {
+            index
+        } else {
+            self.prefix + (index - self.prefix) % self.period
+        }
+    }
+}
+
+/// Finds where `states` starts repeating by hashing every state
+///
+/// Returns the cycle together with the distinct states, so the state at any
+/// index can be read with [`Cycle::reduce`]. Each state is compared only with
+/// the earlier states that share its hash, so this is linear on the number of
+/// distinct states. `None` if the states run out before repeating.
+pub fn find_cycle<T: Hash + Eq>(states: impl IntoIterator<Item = T>) -> Option<(Cycle, Vec<T>)> {
+    let hasher = RandomState::new();
+    let mut seen = HashMap::<u64, Vec<usize>>::new();
+    let mut history = vec![];
+    for state in states {
+        let hash = hasher.hash_one(&state);
+        let same_hash = seen.entry(hash).or_default();
+        if let Some(prefix) = same_hash.iter().find(|index| history[**index] == state) {
+            let cycle = Cycle {
+                prefix: *prefix,
+                period: history.len() - prefix,
+            };
+            return Some((cycle, history));
+        }
+        same_hash.push(history.len());
+        history.push(state);
+    }
+    None
+}
+
+/// Finds where the states produced by repeatedly applying `step` to `initial`
+/// start repeating, with Brent's algorithm
+///
+/// Only two states are kept at a time, at the cost of recomputing steps. The
+/// states must come from a finite space, or this never returns.
+pub fn find_cycle_brent<T: Eq + Clone>(initial: T, mut step: impl FnMut(&T) -> T) -> Cycle {
+    // Search powers of two until the hare meets the tortoise, which gives the period
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // With the hare a period ahead, both meet at the start of the cycle
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_cycle, find_cycle_brent, Cycle};
+
+    #[test]
+    fn cycle_test() {
+        let step = |x: &u64| (x * x + 1) % 255;
+        for initial in 0..255 {
+            let states = std::iter::successors(Some(initial), |x| Some(step(x)))
+                .take(600)
+                .collect::<Vec<_>>();
+            // A map on 255 values repeats well before 600 steps
+            let prefix = (0..)
+                .find(|i| states[(i + 1)..].contains(&states[*i]))
+                .unwrap_or_default();
+            let period = (1..)
+                .find(|period| states[prefix + period] == states[prefix])
+                .unwrap_or_default();
+            let expected = Cycle { prefix, period };
+
+            assert_eq!(find_cycle_brent(initial, step), expected);
+            let found = find_cycle(states.iter().copied());
+            assert_eq!(found.as_ref().map(|(cycle, _)| *cycle), Some(expected));
+            if let Some((cycle, history)) = found {
+                assert_eq!(history.len(), prefix + period);
+                assert_eq!(history[cycle.reduce(599)], states[599]);
+            }
+        }
+
+        assert_eq!(find_cycle([1, 2, 3]), None);
+        let cycle = Cycle {
+            prefix: 3,
+            period: 4,
+        };
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(1_000_000_000), 3 + (1_000_000_000 - 3) % 4);
+    }
+}
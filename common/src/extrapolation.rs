@@ -0,0 +1,222 @@
+/// Exact fraction, always stored with a positive denominator and in lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Result<Self, String> {
+        if denominator == 0 {
+            Err("Rational had a zero denominator.")?;
+        }
+        let gcd = gcd(numerator, denominator);
+        let sign = denominator.signum();
+        Ok(Self {
+            numerator: sign * numerator / gcd,
+            denominator: sign * denominator / gcd,
+        })
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, String> {
+        let numerator = self
+            .numerator
+            .checked_mul(other.denominator)
+            .zip(other.numerator.checked_mul(self.denominator))
+            .and_then(|(lhs, rhs)| lhs.checked_add(rhs));
+        let denominator = self.denominator.checked_mul(other.denominator);
+        match numerator.zip(denominator) {
+            Some((numerator, denominator)) => Self::new(numerator, denominator),
+            None => Err(OVERFLOW.to_owned()),
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Result<Self, String> {
+        let numerator = self.numerator.checked_mul(other.numerator);
+        let denominator = self.denominator.checked_mul(other.denominator);
+        match numerator.zip(denominator) {
+            Some((numerator, denominator)) => Self::new(numerator, denominator),
+            None => Err(OVERFLOW.to_owned()),
+        }
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+const OVERFLOW: &str = "Extrapolation overflowed.";
+
+fn gcd(lhs: i128, rhs: i128) -> i128 {
+    let (mut lhs, mut rhs) = (lhs.abs(), rhs.abs());
+    while rhs != 0 {
+        (lhs, rhs) = (rhs, lhs % rhs);
+    }
+    lhs.max(1)
+}
+
+/// Polynomial that passes through every value of a history, stored as the
+/// leading values of its forward-difference table
+///
+/// The value at index `x` is `sum(differences[k] * binomial(x, k))`, with the
+/// first value of the history at index 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewtonPolynomial {
+    differences: Vec<i128>,
+    len: usize,
+}
+
+impl NewtonPolynomial {
+    /// Builds the polynomial from a history, stopping at a row of differences
+    /// that is all zeros or a single value, which then is the constant term
+    pub fn from_history(history: &[i64]) -> Result<Self, String> {
+        let mut row = history
+            .iter()
+            .map(|value| i128::from(*value))
+            .collect::<Vec<_>>();
+        let mut differences = vec![];
+        while !row.iter().all(|diff| diff.eq(&0)) {
+            differences.push(row[0]);
+            if row.len() == 1 {
+                break;
+            }
+            row = row
+                .windows(2)
+                .map(|wind| wind[1].checked_sub(wind[0]))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(OVERFLOW.to_owned())?;
+        }
+        Ok(Self {
+            differences,
+            len: history.len(),
+        })
+    }
+
+    /// Degree of the polynomial, `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    /// Value of the polynomial at `index`, which may be negative or past the end of the history
+    pub fn evaluate(&self, index: i128) -> Result<i128, String> {
+        let mut binomial = 1i128;
+        let mut value = 0i128;
+        for (k, difference) in (0i128..).zip(&self.differences) {
+            value = difference
+                .checked_mul(binomial)
+                .and_then(|term| value.checked_add(term))
+                .ok_or(OVERFLOW.to_owned())?;
+            binomial = binomial.checked_mul(index - k).ok_or(OVERFLOW.to_owned())? / (k + 1);
+        }
+        Ok(value)
+    }
+
+    /// Value `steps` after the last value of the history
+    pub fn forward(&self, steps: u64) -> Result<i128, String> {
+        let last = i128::try_from(self.len).map_err(|err| err.to_string())? - 1;
+        self.evaluate(last + i128::from(steps))
+    }
+
+    /// Value `steps` before the first value of the history
+    pub fn backward(&self, steps: u64) -> Result<i128, String> {
+        self.evaluate(-i128::from(steps))
+    }
+
+    /// Coefficients of the polynomial in increasing powers of the index
+    pub fn coefficients(&self) -> Result<Vec<Rational>, String> {
+        let mut coefficients = vec![Rational::from(0); self.differences.len()];
+        // Falling factorial `x (x - 1) ... (x - k + 1)` in increasing powers
+        let mut falling = vec![1i128];
+        let mut factorial = 1i128;
+        for (k, difference) in (0i128..).zip(&self.differences) {
+            if k > 0 {
+                factorial = factorial.checked_mul(k).ok_or(OVERFLOW.to_owned())?;
+            }
+            let scale = Rational::new(*difference, factorial)?;
+            for (coefficient, falling) in coefficients.iter_mut().zip(&falling) {
+                *coefficient = coefficient.checked_add(scale.checked_mul((*falling).into())?)?;
+            }
+
+            let mut next = vec![0i128; falling.len() + 1];
+            for (power, value) in falling.iter().enumerate() {
+                next[power + 1] = next[power + 1]
+                    .checked_add(*value)
+                    .ok_or(OVERFLOW.to_owned())?;
+                next[power] = value
+                    .checked_mul(k)
+                    .and_then(|scaled| next[power].checked_sub(scaled))
+                    .ok_or(OVERFLOW.to_owned())?;
+            }
+            falling = next;
+        }
+        Ok(coefficients)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NewtonPolynomial, Rational};
+
+    #[test]
+    fn extrapolation_test() -> Result<(), String> {
+        let polynomial = NewtonPolynomial::from_history(&[10, 13, 16, 21, 30, 45])?;
+        assert_eq!(polynomial.degree(), Some(3));
+        assert_eq!(polynomial.forward(1), Ok(68));
+        assert_eq!(polynomial.backward(1), Ok(5));
+        assert_eq!(polynomial.evaluate(2), Ok(16));
+
+        let triangular = NewtonPolynomial::from_history(&[1, 3, 6, 10, 15, 21])?;
+        assert_eq!(
+            triangular.coefficients()?,
+            [
+                Rational::from(1),
+                Rational::new(3, 2)?,
+                Rational::new(1, 2)?
+            ]
+        );
+        assert_eq!(
+            triangular.forward(1_000_000_000),
+            Ok(500_000_006_500_000_021)
+        );
+
+        assert!(NewtonPolynomial::from_history(&[0, 0, 0, 0, 1])
+            .and_then(|polynomial| polynomial.forward(u64::MAX))
+            .is_err());
+
+        // A single value is a constant
+        let constant = NewtonPolynomial::from_history(&[5])?;
+        assert_eq!(constant.degree(), Some(0));
+        assert_eq!(constant.forward(1), Ok(5));
+        assert_eq!(constant.backward(1), Ok(5));
+
+        // Differences end on the single nonzero value `1`
+        let short = NewtonPolynomial::from_history(&[1, 2, 4])?;
+        assert_eq!(short.degree(), Some(2));
+        assert_eq!(short.forward(1), Ok(7));
+        assert_eq!(short.backward(1), Ok(1));
+        Ok(())
+    }
+}
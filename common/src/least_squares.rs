@@ -0,0 +1,242 @@
+/// Polynomial fitted to a noisy history by least squares
+///
+/// The history is indexed like [`crate::extrapolation::NewtonPolynomial`], with
+/// the first value at index 0. Indices are mapped onto `[-1, 1]` and the
+/// polynomial is expressed in the Chebyshev basis, solved through a QR
+/// decomposition, to keep high degree fits well conditioned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeastSquaresFit {
+    /// Coefficients of the Chebyshev polynomials of the scaled index
+    coefficients: Vec<f64>,
+    /// Inverse of the `R` factor, used to estimate the prediction error
+    r_inverse: Vec<Vec<f64>>,
+    residuals: Vec<f64>,
+    variance: f64,
+    center: f64,
+    scale: f64,
+    len: usize,
+}
+
+/// Extrapolated value together with its standard error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    pub value: f64,
+    pub standard_error: f64,
+}
+
+impl LeastSquaresFit {
+    /// Fits a polynomial of `degree` to `history`
+    pub fn fit(history: &[f64], degree: usize) -> Result<Self, String> {
+        let terms = degree + 1;
+        if history.len() < terms {
+            Err(format!(
+                "History of length {} is too short to fit a polynomial of degree {degree}.",
+                history.len()
+            ))?;
+        }
+        let len = history.len() as f64;
+        let center = (len - 1.) / 2.;
+        let scale = center.max(1.);
+
+        // Columns of the design matrix, orthonormalised in place into `Q`
+        let mut columns = (0..terms)
+            .map(|term| {
+                (0..history.len())
+                    .map(|index| chebyshev((index as f64 - center) / scale, terms)[term])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut r = vec![vec![0.; terms]; terms];
+        for column in 0..terms {
+            // Orthogonalise twice to recover the precision lost in the first pass
+            for _ in 0..2 {
+                for previous in 0..column {
+                    let projection = dot(&columns[previous], &columns[column]);
+                    r[previous][column] += projection;
+                    let (done, rest) = columns.split_at_mut(column);
+                    for (value, basis) in rest[0].iter_mut().zip(&done[previous]) {
+                        *value -= projection * basis;
+                    }
+                }
+            }
+            let norm = dot(&columns[column], &columns[column]).sqrt();
+            if norm < f64::EPSILON {
+                Err("Design matrix was singular.")?;
+            }
+            r[column][column] = norm;
+            columns[column].iter_mut().for_each(|value| *value /= norm);
+        }
+
+        let r_inverse = invert_upper_triangular(&r);
+        let projections = columns
+            .iter()
+            .map(|column| dot(column, history))
+            .collect::<Vec<_>>();
+        let coefficients = r_inverse
+            .iter()
+            .map(|row| dot(row, &projections))
+            .collect::<Vec<f64>>();
+
+        let residuals = history
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                value
+                    - dot(
+                        &chebyshev((index as f64 - center) / scale, terms),
+                        &coefficients,
+                    )
+            })
+            .collect::<Vec<_>>();
+        let degrees_of_freedom = history.len() - terms;
+        let variance = if degrees_of_freedom == 0 {
+            0.
+        } else {
+            residuals
+                .iter()
+                .map(|residual| residual.powi(2))
+                .sum::<f64>()
+                / degrees_of_freedom as f64
+        };
+
+        Ok(Self {
+            coefficients,
+            r_inverse,
+            residuals,
+            variance,
+            center,
+            scale,
+            len: history.len(),
+        })
+    }
+
+    /// Fits polynomials up to `max_degree` and keeps the one with the lowest
+    /// Bayesian information criterion
+    pub fn fit_auto(history: &[f64], max_degree: usize) -> Result<Self, String> {
+        // Leave at least one degree of freedom to estimate the noise
+        let max_degree = max_degree.min(history.len().saturating_sub(2));
+        let len = history.len() as f64;
+        (0..=max_degree)
+            .map(|degree| Self::fit(history, degree))
+            .try_fold(None::<(f64, Self)>, |best, fit| -> Result<_, String> {
+                let fit = fit?;
+                let squared_error = fit.squared_error().max(f64::EPSILON);
+                let criterion =
+                    len * (squared_error / len).ln() + fit.coefficients.len() as f64 * len.ln();
+                Ok(match best {
+                    Some((best_criterion, _)) if best_criterion <= criterion => best,
+                    _ => Some((criterion, fit)),
+                })
+            })?
+            .map(|(_, fit)| fit)
+            .ok_or("History was too short to be fitted.".to_owned())
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    /// Difference between each value of the history and the fitted polynomial
+    pub fn residuals(&self) -> &[f64] {
+        &self.residuals
+    }
+
+    pub fn squared_error(&self) -> f64 {
+        self.residuals.iter().map(|residual| residual.powi(2)).sum()
+    }
+
+    /// Value of the fitted polynomial at `index`
+    pub fn evaluate(&self, index: f64) -> Prediction {
+        let basis = chebyshev((index - self.center) / self.scale, self.coefficients.len());
+        // The variance of the prediction is `variance * |R^-T basis|^2`
+        let leverage = (0..basis.len())
+            .map(|column| {
+                self.r_inverse
+                    .iter()
+                    .zip(&basis)
+                    .map(|(row, value)| row[column] * value)
+                    .sum::<f64>()
+                    .powi(2)
+            })
+            .sum::<f64>();
+        Prediction {
+            value: dot(&basis, &self.coefficients),
+            standard_error: (self.variance * leverage).sqrt(),
+        }
+    }
+
+    /// Value `steps` after the last value of the history
+    pub fn forward(&self, steps: u64) -> Prediction {
+        self.evaluate((self.len as f64 - 1.) + steps as f64)
+    }
+
+    /// Value `steps` before the first value of the history
+    pub fn backward(&self, steps: u64) -> Prediction {
+        self.evaluate(-(steps as f64))
+    }
+}
+
+/// First `terms` Chebyshev polynomials of the first kind evaluated at `x`
+fn chebyshev(x: f64, terms: usize) -> Vec<f64> {
+    let mut values = vec![1., x];
+    while values.len() < terms {
+        let next = 2. * x * values[values.len() - 1] - values[values.len() - 2];
+        values.push(next);
+    }
+    values.truncate(terms);
+    values
+}
+
+fn dot(lhs: &[f64], rhs: &[f64]) -> f64 {
+    lhs.iter().zip(rhs).map(|(lhs, rhs)| lhs * rhs).sum()
+}
+
+/// Inverts a non-singular upper triangular matrix by back substitution
+fn invert_upper_triangular(matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let size = matrix.len();
+    let columns = (0..size)
+        .map(|column| {
+            let mut solution = vec![0.; size];
+            for row in (0..=column).rev() {
+                let identity = if row == column { 1. } else { 0. };
+                let known = dot(&matrix[row][(row + 1)..], &solution[(row + 1)..]);
+                solution[row] = (identity - known) / matrix[row][row];
+            }
+            solution
+        })
+        .collect::<Vec<_>>();
+    (0..size)
+        .map(|row| columns.iter().map(|column| column[row]).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LeastSquaresFit;
+
+    #[test]
+    fn least_squares_test() -> Result<(), String> {
+        let exact = [10., 13., 16., 21., 30., 45.];
+        let fit = LeastSquaresFit::fit(&exact, 3)?;
+        assert!((fit.forward(1).value - 68.).abs() < 1e-6);
+        assert!((fit.backward(1).value - 5.).abs() < 1e-6);
+        assert!(fit.residuals().iter().all(|residual| residual.abs() < 1e-6));
+
+        let noisy = (0..20)
+            .map(|x| f64::from(x * x + 3 * x + if x == 7 { 5 } else { 0 }))
+            .collect::<Vec<_>>();
+        let fit = LeastSquaresFit::fit_auto(&noisy, 6)?;
+        assert_eq!(fit.degree(), 2);
+        let prediction = fit.forward(1);
+        assert!((prediction.value - 460.).abs() < 2.);
+        assert!(prediction.standard_error > 0.);
+        let worst = fit
+            .residuals()
+            .iter()
+            .enumerate()
+            .max_by(|lhs, rhs| lhs.1.abs().total_cmp(&rhs.1.abs()))
+            .map(|(index, _)| index);
+        assert_eq!(worst, Some(7));
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod blocks;
+pub mod cycle;
+pub mod extrapolation;
+pub mod least_squares;
+pub mod ppm;
@@ -0,0 +1,41 @@
+use std::io::Write;
+
+/// Writes a binary portable pixmap (P6) of `width` by `height` pixels, given
+/// as rows of red, green and blue bytes
+pub fn write_p6(
+    writer: &mut impl Write,
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+) -> std::io::Result<()> {
+    if Some(pixels.len())
+        != width
+            .checked_mul(height)
+            .and_then(|area| area.checked_mul(3))
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Expected {width}x{height} pixels, got {} bytes.",
+                pixels.len()
+            ),
+        ));
+    }
+    write!(writer, "P6\n{width} {height}\n255\n")?;
+    writer.write_all(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_p6;
+
+    #[test]
+    fn write_p6_test() -> Result<(), String> {
+        let mut image = vec![];
+        write_p6(&mut image, 2, 1, &[255, 0, 0, 0, 0, 255]).map_err(|err| err.to_string())?;
+        assert_eq!(image, b"P6\n2 1\n255\n\xff\0\0\0\0\xff");
+
+        assert!(write_p6(&mut vec![], 2, 2, &[0; 6]).is_err());
+        Ok(())
+    }
+}
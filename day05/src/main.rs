@@ -75,4 +75,16 @@ humidity-to-location map:
     fn part2_test() {
         assert_eq!(super::get_lowest_seed_location(PART1_INPUT, true), Ok(46));
     }
+
+    #[test]
+    fn malformed_separators_test() {
+        let crlf = PART1_INPUT
+            .replace('\n', "\r\n")
+            .replace("\r\n\r\n", "\r\n\r\n\r\n");
+        assert_eq!(super::get_lowest_seed_location(&crlf, false), Ok(35));
+        assert_eq!(
+            super::get_lowest_seed_location(&PART1_INPUT.replace("52 50 48", "52 50"), false),
+            Err("Block 2, line 5: Line had wrong number of items. '[52, 50]'".to_owned())
+        );
+    }
 }
@@ -1,4 +1,6 @@
-use std::{ops::Range, str::Lines};
+use std::ops::Range;
+
+use common::blocks::{blocks, Block};
 
 type MappingSection = Vec<(Range<u64>, Range<u64>)>;
 
@@ -106,35 +108,46 @@ impl SeedMapper {
         )
     }
 
-    fn read_almanac_section(
+    fn read_almanac_section<'a>(
         section_title: &str,
-        lines: &mut Lines,
+        sections: &mut impl Iterator<Item = &'a Block<'a>>,
     ) -> Result<MappingSection, String> {
-        let header = lines
+        let section = sections
             .next()
             .ok_or(format!("EOF before start of '{section_title}' section."))?;
-        if header.ne(section_title) {
-            Err(format!(
-                "Section header is wrong. Expected: '{section_title}', Got: '{header}'"
-            ))?;
+        let mut lines = section.numbered_lines();
+        if let Some((line_number, header)) = lines.next() {
+            if header.ne(section_title) {
+                Err(section.error(
+                    line_number,
+                    &format!(
+                        "Section header is wrong. Expected: '{section_title}', Got: '{header}'"
+                    ),
+                ))?;
+            }
         }
         let mut res = vec![];
 
-        for line in lines.by_ref() {
-            if line.is_empty() {
-                break;
-            }
+        for (line_number, line) in lines {
             let ranges_start_and_len = line
                 .split_whitespace()
                 .map(str::parse::<u64>)
                 .collect::<Result<Vec<u64>, _>>()
-                .map_err(|err| format!("Failed to read ranges start and len. '{err}'"))?;
+                .map_err(|err| {
+                    section.error(
+                        line_number,
+                        &format!("Failed to read ranges start and len. '{err}'"),
+                    )
+                })?;
             match ranges_start_and_len.as_slice() {
                 [dest_start, source_start, len] => res.push((
                     *source_start..(source_start + len),
                     *dest_start..(dest_start + len),
                 )),
-                a => Err(format!("Line had wrong number of items. '{a:?}'"))?,
+                a => Err(section.error(
+                    line_number,
+                    &format!("Line had wrong number of items. '{a:?}'"),
+                ))?,
             }
         }
 
@@ -145,10 +158,15 @@ impl SeedMapper {
 impl TryFrom<&str> for SeedMapper {
     type Error = String;
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        let mut lines = input.lines();
+        let blocks = blocks(input);
+        let mut sections = blocks.iter();
 
         let seeds = {
-            let first_line = lines.next().ok_or("Failed to read first line.")?;
+            let seeds_section = sections.next().ok_or("Failed to read first line.")?;
+            if seeds_section.lines().len() != 1 {
+                Err("There was no spacing between 'seeds' and 'seed-to-soil' sections.")?;
+            }
+            let first_line = seeds_section.lines()[0];
             let split = first_line
                 .split_once(':')
                 .ok_or("Failed to split first line.")?;
@@ -164,22 +182,18 @@ impl TryFrom<&str> for SeedMapper {
             }
         };
 
-        let spacing = lines.next().ok_or("Input only had one line.")?;
-        if !spacing.is_empty() {
-            Err("There was no spacing between 'seeds' and 'seed-to-soil' sections.")?;
-        }
-
-        let seed_to_soil = Self::read_almanac_section("seed-to-soil map:", &mut lines)?;
-        let soil_to_fertilizer = Self::read_almanac_section("soil-to-fertilizer map:", &mut lines)?;
+        let seed_to_soil = Self::read_almanac_section("seed-to-soil map:", &mut sections)?;
+        let soil_to_fertilizer =
+            Self::read_almanac_section("soil-to-fertilizer map:", &mut sections)?;
         let fertilizer_to_water =
-            Self::read_almanac_section("fertilizer-to-water map:", &mut lines)?;
-        let water_to_light = Self::read_almanac_section("water-to-light map:", &mut lines)?;
+            Self::read_almanac_section("fertilizer-to-water map:", &mut sections)?;
+        let water_to_light = Self::read_almanac_section("water-to-light map:", &mut sections)?;
         let light_to_temperature =
-            Self::read_almanac_section("light-to-temperature map:", &mut lines)?;
+            Self::read_almanac_section("light-to-temperature map:", &mut sections)?;
         let temperature_to_humidity =
-            Self::read_almanac_section("temperature-to-humidity map:", &mut lines)?;
+            Self::read_almanac_section("temperature-to-humidity map:", &mut sections)?;
         let humidity_to_location =
-            Self::read_almanac_section("humidity-to-location map:", &mut lines)?;
+            Self::read_almanac_section("humidity-to-location map:", &mut sections)?;
 
         Ok(SeedMapper {
             seeds,
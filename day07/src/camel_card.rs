@@ -1,5 +1,5 @@
 #[allow(clippy::module_name_repetitions)]
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CamelCardRank {
     HighCard,
     OnePair,
@@ -10,6 +10,32 @@ pub enum CamelCardRank {
     FiveOfAKind,
 }
 
+impl std::fmt::Display for CamelCardRank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::HighCard => "HighCard",
+            Self::OnePair => "OnePair",
+            Self::TwoPairs => "TwoPairs",
+            Self::ThreeOfAKind => "ThreeOfAKind",
+            Self::FullHouse => "FullHouse",
+            Self::FourOfAKind => "FourOfAKind",
+            Self::FiveOfAKind => "FiveOfAKind",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A hand that can explain how it was ranked
+#[allow(clippy::module_name_repetitions)]
+pub trait RankedHand {
+    /// The rank the hand was classified as
+    fn rank(&self) -> CamelCardRank;
+    /// The card the jokers were substituted by to reach [`RankedHand::rank`], if any
+    fn joker_substitution(&self) -> Option<char> {
+        None
+    }
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, PartialEq, Eq)]
 pub struct CamelCardHand<'a> {
@@ -76,6 +102,12 @@ impl<'a> From<&'a str> for CamelCardHand<'a> {
     }
 }
 
+impl RankedHand for CamelCardHand<'_> {
+    fn rank(&self) -> CamelCardRank {
+        self.rank
+    }
+}
+
 impl<'a> PartialOrd for CamelCardHand<'a> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -118,6 +150,7 @@ impl<'a> Ord for CamelCardHand<'a> {
 pub struct JokerCamelCardHand<'a> {
     rank: CamelCardRank,
     hand: &'a str,
+    substitution: Option<char>,
 }
 
 impl<'a> From<&'a str> for JokerCamelCardHand<'a> {
@@ -146,7 +179,47 @@ impl<'a> From<&'a str> for JokerCamelCardHand<'a> {
             _ => CamelCardRank::FiveOfAKind,
         };
 
-        Self { rank, hand: value }
+        let substitution = if cards.len() == value.chars().count() {
+            None
+        } else {
+            Some(best_joker_substitution(&cards))
+        };
+
+        Self {
+            rank,
+            hand: value,
+            substitution,
+        }
+    }
+}
+
+/// Finds the card that jokers should mimic, the most common card on the hand,
+/// with ties broken by the strongest card
+fn best_joker_substitution(sorted_cards: &[char]) -> char {
+    sorted_cards
+        .chunk_by(|lhs, rhs| lhs == rhs)
+        .max_by_key(|group| (group.len(), card_strength(group[0])))
+        .map_or('A', |group| group[0])
+}
+
+fn card_strength(card: char) -> u32 {
+    match card {
+        'A' => 14,
+        'K' => 13,
+        'Q' => 12,
+        'J' => 11,
+        'T' => 10,
+        card => card.to_digit(10).unwrap_or(0),
+    }
+}
+
+impl RankedHand for JokerCamelCardHand<'_> {
+    fn rank(&self) -> CamelCardRank {
+        self.rank
+    }
+
+    fn joker_substitution(&self) -> Option<char> {
+        self.substitution
     }
 }
 
@@ -2,9 +2,27 @@ mod camel_card;
 
 use std::{io::Read, num::TryFromIntError};
 
-use camel_card::{CamelCardHand, JokerCamelCardHand};
+use camel_card::{CamelCardHand, CamelCardRank, JokerCamelCardHand, RankedHand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// How a single hand was ranked and how much it won
+#[derive(Debug, PartialEq, Eq)]
+struct HandReport<'a> {
+    hand: &'a str,
+    rank: CamelCardRank,
+    joker_substitution: Option<char>,
+    position: u32,
+    bid: u32,
+    winnings: u32,
+}
 
 fn main() -> Result<(), String> {
+    let (report, jokers) = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day07_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -18,6 +36,15 @@ fn main() -> Result<(), String> {
                     let part2 = calculate_game_winnings::<JokerCamelCardHand>(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if let Some(format) = report {
+                        let report = if jokers {
+                            explain_game_winnings::<JokerCamelCardHand>(&input)?
+                        } else {
+                            explain_game_winnings::<CamelCardHand>(&input)?
+                        };
+                        print!("{}", format_report(&report, format));
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -27,32 +54,121 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn calculate_game_winnings<'a, T: Ord + From<&'a str>>(input: &'a str) -> Result<u32, String> {
+/// Parses `--report <csv|json>` and `--jokers`
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<(Option<ReportFormat>, bool), String> {
+    let mut report = None;
+    let mut jokers = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--report" => {
+                report = match args.next().as_deref() {
+                    Some("csv") => Some(ReportFormat::Csv),
+                    Some("json") => Some(ReportFormat::Json),
+                    Some(other) => return Err(format!("Unknown report format '{other}'.")),
+                    None => return Err("Missing report format.".to_owned()),
+                };
+            }
+            "--jokers" => jokers = true,
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
+    }
+    Ok((report, jokers))
+}
+
+/// Parses the hands and bids, sorted from weakest to strongest hand
+fn ranked_games<'a, T: Ord + From<&'a str>>(
+    input: &'a str,
+) -> Result<Vec<(&'a str, T, u32)>, String> {
     let mut hands = input
         .lines()
         .map(|line| {
             if let Some((hand, bid)) = line.split_once(' ') {
-                let hand: T = hand.into();
+                let ranked: T = hand.into();
                 bid.parse::<u32>()
-                    .map(|bid| (hand, bid))
+                    .map(|bid| (hand, ranked, bid))
                     .map_err(|err| format!("Failed to parse games. '{err}'"))
             } else {
                 Err("Line did not contain game and bid.".to_owned())
             }
         })
         .collect::<Result<Vec<_>, _>>()?;
-    hands.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
-    hands
+    hands.sort_by(|(_, lhs, _), (_, rhs, _)| lhs.cmp(rhs));
+    Ok(hands)
+}
+
+fn calculate_game_winnings<'a, T: Ord + From<&'a str>>(input: &'a str) -> Result<u32, String> {
+    ranked_games::<T>(input)?
         .into_iter()
         .enumerate()
-        .map(|(i, (_, bid))| u32::try_from(i + 1).map(|i| i * bid))
+        .map(|(i, (_, _, bid))| u32::try_from(i + 1).map(|i| i * bid))
         .sum::<Result<u32, TryFromIntError>>()
         .map_err(|err| format!("Failed to calculate hands payouts. '{err}'"))
 }
 
+/// Explains the rank, position and winnings of every hand, sorted from weakest to strongest
+fn explain_game_winnings<'a, T: Ord + RankedHand + From<&'a str>>(
+    input: &'a str,
+) -> Result<Vec<HandReport<'a>>, String> {
+    ranked_games::<T>(input)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, (hand, ranked, bid))| {
+            let position = u32::try_from(i + 1)
+                .map_err(|err| format!("Failed to calculate hands payouts. '{err}'"))?;
+            Ok(HandReport {
+                hand,
+                rank: ranked.rank(),
+                joker_substitution: ranked.joker_substitution(),
+                position,
+                bid,
+                winnings: position * bid,
+            })
+        })
+        .collect()
+}
+
+fn format_report(report: &[HandReport], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Csv => {
+            let mut out = "position,hand,rank,joker_substitution,bid,winnings\n".to_owned();
+            for hand in report {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    hand.position,
+                    hand.hand,
+                    hand.rank,
+                    hand.joker_substitution
+                        .map(String::from)
+                        .unwrap_or_default(),
+                    hand.bid,
+                    hand.winnings
+                ));
+            }
+            out
+        }
+        ReportFormat::Json => {
+            let entries = report
+                .iter()
+                .map(|hand| {
+                    let substitution = hand
+                        .joker_substitution
+                        .map_or("null".to_owned(), |card| format!("\"{card}\""));
+                    format!(
+                        "  {{\"position\": {}, \"hand\": \"{}\", \"rank\": \"{}\", \"joker_substitution\": {substitution}, \"bid\": {}, \"winnings\": {}}}",
+                        hand.position, hand.hand, hand.rank, hand.bid, hand.winnings
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("[\n{}\n]\n", entries.join(",\n"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::camel_card::{CamelCardHand, JokerCamelCardHand};
+    use crate::camel_card::{CamelCardHand, CamelCardRank, JokerCamelCardHand};
 
     const PART1_INPUT: &str = r"32T3K 765
 T55J5 684
@@ -75,4 +191,18 @@ QQQJA 483";
             Ok(5905)
         );
     }
+
+    #[test]
+    fn explain_test() -> Result<(), String> {
+        let report = super::explain_game_winnings::<JokerCamelCardHand>(PART1_INPUT)?;
+        let order = report.iter().map(|hand| hand.hand).collect::<Vec<_>>();
+        assert_eq!(order, ["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]);
+        assert_eq!(report[4].rank, CamelCardRank::FourOfAKind);
+        assert_eq!(report[4].joker_substitution, Some('T'));
+        assert_eq!(report[1].joker_substitution, None);
+        assert_eq!(report.iter().map(|hand| hand.winnings).sum::<u32>(), 5905);
+        assert!(super::format_report(&report[..1], super::ReportFormat::Csv)
+            .ends_with("1,32T3K,OnePair,,765,765\n"));
+        Ok(())
+    }
 }
@@ -0,0 +1,90 @@
+use crate::network::DesertNetwork;
+
+/// Precomputed jumps over whole passes of the instruction order, allowing the
+/// network to be advanced by any number of steps in logarithmic time
+#[derive(Debug)]
+pub struct JumpTable<'n, 'a> {
+    network: &'n DesertNetwork<'a>,
+    /// `lifts[k][node]` is the node reached after `2^k` passes and whether a
+    /// target node was reached during those passes
+    lifts: Vec<Vec<(usize, bool)>>,
+    /// First step, inside a single pass, on which a target node is reached
+    first_hit: Vec<Option<u64>>,
+}
+
+impl<'n, 'a> JumpTable<'n, 'a> {
+    pub fn new(network: &'n DesertNetwork<'a>, target: impl Fn(&str) -> bool) -> Self {
+        let (pass, first_hit) = (0..network.node_count())
+            .map(|start| {
+                let mut node = start;
+                let mut first_hit = None;
+                for (instruction, step) in (0..network.pass_length()).zip(1..) {
+                    node = network.step(node, instruction);
+                    if first_hit.is_none() && target(network.label(node)) {
+                        first_hit = Some(step);
+                    }
+                }
+                ((node, first_hit.is_some()), first_hit)
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+
+        let mut lifts = vec![pass];
+        while lifts.len() < 64 {
+            let Some(last) = lifts.last() else {
+                break;
+            };
+            let next = last
+                .iter()
+                .map(|(middle, hit)| {
+                    let (end, second_hit) = last[*middle];
+                    (end, *hit || second_hit)
+                })
+                .collect();
+            lifts.push(next);
+        }
+
+        Self {
+            network,
+            lifts,
+            first_hit,
+        }
+    }
+
+    /// Node reached after taking `steps` steps from `node`
+    pub fn advance(&self, mut node: usize, steps: u64) -> usize {
+        let pass_length = self.network.pass_length() as u64;
+        let passes = steps / pass_length;
+        for (k, lift) in self.lifts.iter().enumerate() {
+            if passes & (1 << k) != 0 {
+                node = lift[node].0;
+            }
+        }
+        for instruction in 0..(steps % pass_length) as usize {
+            node = self.network.step(node, instruction);
+        }
+        node
+    }
+
+    /// Number of steps from `node` until a target node is reached, or `None` if
+    /// no target is ever reached
+    pub fn first_hit(&self, mut node: usize) -> Result<Option<u64>, String> {
+        let mut passes = 0u64;
+        if self.first_hit[node].is_none() {
+            for (k, lift) in self.lifts.iter().enumerate().rev() {
+                if !lift[node].1 {
+                    node = lift[node].0;
+                    passes |= 1 << k;
+                }
+            }
+            if !self.lifts[0][node].1 {
+                return Ok(None);
+            }
+        }
+        passes
+            .checked_mul(self.network.pass_length() as u64)
+            .zip(self.first_hit[node])
+            .and_then(|(steps, offset)| steps.checked_add(offset))
+            .map(Some)
+            .ok_or("Steps until target overflowed.".to_owned())
+    }
+}
@@ -1,5 +1,11 @@
+mod jump_table;
+mod network;
+
 use std::io::Read;
 
+use jump_table::JumpTable;
+use network::DesertNetwork;
+
 #[derive(Debug)]
 struct MapInstruction<'a> {
     label: &'a str,
@@ -7,12 +13,20 @@ struct MapInstruction<'a> {
     right: &'a str,
 }
 
+#[derive(Debug)]
 enum InstructionSide {
     Left,
     Right,
 }
 
+#[derive(Debug, Default)]
+struct Options {
+    dot: Option<Option<String>>,
+    advance: Option<(String, u64)>,
+}
+
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day08_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -26,6 +40,18 @@ fn main() -> Result<(), String> {
                     let part2 = follow_ghost_map(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    let (instruction_order, instructions) = process_input(&input)?;
+                    let network = DesertNetwork::new(instruction_order, &instructions)?;
+                    if let Some((start, steps)) = options.advance {
+                        let timer = std::time::Instant::now();
+                        let jump_table = JumpTable::new(&network, |_| false);
+                        let node = jump_table.advance(network.id(&start)?, steps);
+                        println!("{:?}: {}", timer.elapsed(), network.label(node));
+                    }
+                    if let Some(from) = options.dot {
+                        print!("{}", network.to_dot(from.as_deref())?);
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -35,7 +61,35 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn process_input(input: &str) -> Result<(Vec<InstructionSide>, Vec<MapInstruction>), String> {
+/// Parses `--dot [START]`, requesting the network to be printed as a DOT graph,
+/// and `--advance START STEPS`, requesting the node reached after `STEPS` steps
+///
+/// Values starting with `--` are never taken as a node label, so `--dot` followed
+/// by another flag prints the whole network.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut args = args.peekable();
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dot" => options.dot = Some(args.next_if(|value| !value.starts_with("--"))),
+            "--advance" => {
+                let start = args
+                    .next_if(|value| !value.starts_with("--"))
+                    .ok_or("Missing start node.".to_owned())?;
+                let steps = args
+                    .next()
+                    .ok_or("Missing number of steps.".to_owned())?
+                    .parse()
+                    .map_err(|err| format!("Failed to parse number of steps. '{err}'"))?;
+                options.advance = Some((start, steps));
+            }
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
+    }
+    Ok(options)
+}
+
+fn process_input(input: &str) -> Result<(Vec<InstructionSide>, Vec<MapInstruction<'_>>), String> {
     let mut lines = input.lines();
     let instrunction_order = {
         let instructions = lines.next().ok_or("Input was empty.".to_owned())?;
@@ -80,91 +134,26 @@ fn process_input(input: &str) -> Result<(Vec<InstructionSide>, Vec<MapInstructio
     }
 }
 
-fn next_node<'a>(node: &str, instructions: &'a [MapInstruction]) -> Option<&'a MapInstruction<'a>> {
-    match instructions.binary_search_by_key(&node, |inst| inst.label) {
-        Ok(idx) => instructions.get(idx),
-        Err(_) => None,
-    }
-}
-
 fn follow_map(input: &str) -> Result<u64, String> {
     const START: &str = "AAA";
     const END: &str = "ZZZ";
 
     let (instruction_order, instructions) = process_input(input)?;
+    let network = DesertNetwork::new(instruction_order, &instructions)?;
+    let start = network
+        .id(START)
+        .map_err(|_| "Could not find start node.".to_owned())?;
 
-    let start = next_node(START, &instructions).ok_or("Could not find start node.".to_owned())?;
-
-    follow_instructios(
-        start,
-        &instructions,
-        &instruction_order,
-        &|_steps: u64, node: &MapInstruction| node.label.ne(END),
-    )
-}
-
-fn follow_instructios(
-    start: &MapInstruction,
-    instructions: &[MapInstruction],
-    instruction_order: &[InstructionSide],
-    test: &dyn Fn(u64, &MapInstruction) -> bool,
-) -> Result<u64, String> {
-    let mut instruction_order_cycle = instruction_order.iter().cycle();
-    let mut node = start;
-    let mut steps = 0;
-    while test(steps, node) {
-        steps += 1;
-        let next = match instruction_order_cycle.next() {
-            Some(InstructionSide::Left) => node.left,
-            Some(InstructionSide::Right) => node.right,
-            None => Err("Cycle returned None.")?,
-        };
-        node = next_node(next, instructions).ok_or(format!("Could not find node '{next}'."))?;
-    }
-    Ok(steps)
+    JumpTable::new(&network, |label| label == END)
+        .first_hit(start)?
+        .ok_or("Could not reach end node.".to_owned())
 }
 
 fn follow_ghost_map(input: &str) -> Result<u64, String> {
-    const PRIMES: &[u64; 60] = &[
-        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
-        97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181,
-        191, 193, 197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281,
-    ];
     let (instruction_order, instructions) = process_input(input)?;
-
-    // Assumption
-    // All start and end pairs are closed cycles
-    let mut cycles = instructions
-        .iter()
-        .filter(|inst| inst.label.ends_with('A'))
-        .map(|start| {
-            follow_instructios(
-                start,
-                &instructions,
-                &instruction_order,
-                &|steps: u64, node: &MapInstruction| steps == 0 || !node.label.ends_with('Z'),
-            )
-        })
-        .collect::<Result<Vec<_>, String>>()?;
-
-    let mut factors = vec![];
-    for prime in PRIMES {
-        while !&cycles.iter().all(|i| i.eq(&1)) {
-            let mut divided_any = false;
-            for c in &mut cycles {
-                if *c % prime == 0 {
-                    *c /= prime;
-                    divided_any = true;
-                }
-            }
-            if divided_any {
-                factors.push(prime);
-            } else {
-                break;
-            }
-        }
-    }
-    Ok(factors.into_iter().product())
+    DesertNetwork::new(instruction_order, &instructions)?
+        .synchronise()?
+        .ok_or("Ghosts never synchronise.".to_owned())
 }
 
 #[cfg(test)]
@@ -208,4 +197,102 @@ XXX = (XXX, XXX)";
         assert_eq!(super::follow_ghost_map(PART1_INPUT2), Ok(6));
         assert_eq!(super::follow_ghost_map(PART2_INPUT1), Ok(6));
     }
+
+    #[test]
+    fn ghost_cycle_test() -> Result<(), String> {
+        let (order, instructions) = super::process_input(PART2_INPUT1)?;
+        let network = super::DesertNetwork::new(order, &instructions)?;
+        let cycle = network.ghost_cycle("22A")?;
+        assert_eq!(
+            cycle,
+            super::network::GhostCycle {
+                prefix: 1,
+                length: 6,
+                prefix_hits: vec![],
+                cycle_hits: vec![3, 6],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_args_test() -> Result<(), String> {
+        let args = |args: &[&str]| super::parse_args(args.iter().map(|arg| arg.to_string()));
+
+        let options = args(&["--dot", "11A"])?;
+        assert_eq!(options.dot, Some(Some("11A".to_owned())));
+
+        let options = args(&["--dot", "--advance", "11A", "3"])?;
+        assert_eq!(options.dot, Some(None));
+        assert_eq!(options.advance, Some(("11A".to_owned(), 3)));
+
+        assert!(args(&["--dot", "--bogus"]).is_err());
+        assert!(args(&["--advance", "--dot", "3"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dot_test() -> Result<(), String> {
+        let (order, instructions) = super::process_input(PART2_INPUT1)?;
+        let network = super::DesertNetwork::new(order, &instructions)?;
+        let dot = network.to_dot(Some("11A"))?;
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("\"11A\" [label=\"11A\", style=filled, fillcolor=palegreen];"));
+        assert!(dot.contains("\"11Z\" [label=\"11Z\", style=filled, fillcolor=salmon];"));
+        assert!(dot.contains("\"11B\" -> \"11Z\" [label=\"R\"];"));
+        assert!(dot.contains("\"XXX\" -> \"XXX\" [label=\"LR\"];"));
+        assert!(!dot.contains("22A"));
+        Ok(())
+    }
+
+    #[test]
+    fn jump_table_test() -> Result<(), String> {
+        let (order, instructions) = super::process_input(PART2_INPUT1)?;
+        let network = super::DesertNetwork::new(order, &instructions)?;
+        let jump_table = super::JumpTable::new(&network, |label| label == "22Z");
+        let start = network.id("22A")?;
+        let mut node = start;
+        for steps in 0..20 {
+            assert_eq!(jump_table.advance(start, steps), node);
+            node = network.step(node, usize::try_from(steps % 2).map_err(|e| e.to_string())?);
+        }
+        assert_eq!(
+            network.label(jump_table.advance(start, 3_000_000_001)),
+            "22B"
+        );
+        assert_eq!(jump_table.first_hit(start), Ok(Some(3)));
+        assert_eq!(jump_table.first_hit(network.id("11A")?), Ok(None));
+        Ok(())
+    }
+
+    #[test]
+    fn ghost_offsets_test() {
+        // 11A reaches 11Z on steps 2, 4, 6..., 22A on 4, 9, 14...
+        const OFFSETS: &str = r"L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22D, 22D)
+22D = (22Z, 22Z)
+22Z = (22E, 22E)
+22E = (22B, 22B)";
+        assert_eq!(super::follow_ghost_map(OFFSETS), Ok(4));
+
+        const NEVER: &str = r"L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22C, 22C)
+22C = (22Z, 22Z)";
+        assert_eq!(
+            super::follow_ghost_map(NEVER),
+            Err("Ghosts never synchronise.".to_owned())
+        );
+    }
 }
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use crate::{InstructionSide, MapInstruction};
+
+/// Path of a ghost through the network until it starts repeating
+#[derive(Debug, PartialEq, Eq)]
+pub struct GhostCycle {
+    /// Number of steps before the ghost enters its cycle
+    pub prefix: u64,
+    /// Number of steps it takes to go around the cycle once
+    pub length: u64,
+    /// Steps, before entering the cycle, on which the ghost is on a `Z` node
+    pub prefix_hits: Vec<u64>,
+    /// Steps, inside the first lap of the cycle, on which the ghost is on a `Z` node
+    pub cycle_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    fn hits_at(&self, step: u64) -> bool {
+        if step < self.prefix {
+            self.prefix_hits.contains(&step)
+        } else {
+            let offset = self.prefix + (step - self.prefix) % self.length;
+            self.cycle_hits.contains(&offset)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DesertNetwork<'a> {
+    labels: Vec<&'a str>,
+    /// Id of each label, its position in `labels`
+    ids: HashMap<&'a str, usize>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    instruction_order: Vec<InstructionSide>,
+}
+
+impl<'a> DesertNetwork<'a> {
+    pub fn new(
+        instruction_order: Vec<InstructionSide>,
+        instructions: &[MapInstruction<'a>],
+    ) -> Result<Self, String> {
+        if instruction_order.is_empty() {
+            Err("Instruction order was empty.")?;
+        }
+        let ids = instructions
+            .iter()
+            .enumerate()
+            .map(|(id, inst)| (inst.label, id))
+            .collect::<HashMap<_, _>>();
+        let find = |label: &str| {
+            ids.get(label)
+                .copied()
+                .ok_or(format!("Could not find node '{label}'."))
+        };
+
+        let (left, right) = instructions
+            .iter()
+            .map(|inst| Ok((find(inst.left)?, find(inst.right)?)))
+            .collect::<Result<(Vec<_>, Vec<_>), String>>()?;
+
+        Ok(Self {
+            labels: instructions.iter().map(|inst| inst.label).collect(),
+            ids,
+            left,
+            right,
+            instruction_order,
+        })
+    }
+
+    pub fn starts(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.labels
+            .iter()
+            .copied()
+            .filter(|label| label.ends_with('A'))
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn pass_length(&self) -> usize {
+        self.instruction_order.len()
+    }
+
+    pub fn label(&self, node: usize) -> &'a str {
+        self.labels[node]
+    }
+
+    pub fn id(&self, label: &str) -> Result<usize, String> {
+        self.ids
+            .get(label)
+            .copied()
+            .ok_or(format!("Could not find node '{label}'."))
+    }
+
+    /// Renders the network as a Graphviz DOT graph, optionally only the part
+    /// reachable from `from`
+    pub fn to_dot(&self, from: Option<&str>) -> Result<String, String> {
+        let mut included = vec![from.is_none(); self.labels.len()];
+        if let Some(from) = from {
+            let mut queue = vec![self.id(from)?];
+            while let Some(node) = queue.pop() {
+                if !included[node] {
+                    included[node] = true;
+                    queue.extend([self.left[node], self.right[node]]);
+                }
+            }
+        }
+
+        let mut dot = "digraph network {\n".to_owned();
+        for (node, label) in self.labels.iter().enumerate() {
+            if !included[node] {
+                continue;
+            }
+            let style = if label.ends_with('A') {
+                ", style=filled, fillcolor=palegreen"
+            } else if label.ends_with('Z') {
+                ", style=filled, fillcolor=salmon"
+            } else {
+                ""
+            };
+            dot.push_str(&format!("    \"{label}\" [label=\"{label}\"{style}];\n"));
+        }
+        for (node, label) in self.labels.iter().enumerate() {
+            if !included[node] {
+                continue;
+            }
+            let (left, right) = (self.labels[self.left[node]], self.labels[self.right[node]]);
+            if left == right {
+                dot.push_str(&format!("    \"{label}\" -> \"{left}\" [label=\"LR\"];\n"));
+            } else {
+                dot.push_str(&format!("    \"{label}\" -> \"{left}\" [label=\"L\"];\n"));
+                dot.push_str(&format!("    \"{label}\" -> \"{right}\" [label=\"R\"];\n"));
+            }
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
+    pub fn step(&self, node: usize, instruction: usize) -> usize {
+        match self.instruction_order[instruction] {
+            InstructionSide::Left => self.left[node],
+            InstructionSide::Right => self.right[node],
+        }
+    }
+
+    /// Walks from `start` until a `(node, instruction)` state repeats
+    pub fn ghost_cycle(&self, start: &str) -> Result<GhostCycle, String> {
+        let mut seen = HashMap::new();
+        let mut node = self.id(start)?;
+        let mut step = 0u64;
+        let mut instruction = 0;
+        let mut hits = vec![];
+
+        loop {
+            if let Some(first_seen) = seen.insert((node, instruction), step) {
+                let (prefix_hits, cycle_hits) = hits.into_iter().partition(|hit| *hit < first_seen);
+                return Ok(GhostCycle {
+                    prefix: first_seen,
+                    length: step - first_seen,
+                    prefix_hits,
+                    cycle_hits,
+                });
+            }
+            if self.labels[node].ends_with('Z') {
+                hits.push(step);
+            }
+            node = self.step(node, instruction);
+            instruction = (instruction + 1) % self.instruction_order.len();
+            step += 1;
+        }
+    }
+
+    /// Finds the first step, after leaving the starting nodes, on which all ghosts
+    /// are on a `Z` node, or `None` if the ghosts never synchronise
+    pub fn synchronise(&self) -> Result<Option<u64>, String> {
+        let cycles = self
+            .starts()
+            .map(|start| self.ghost_cycle(start))
+            .collect::<Result<Vec<_>, String>>()?;
+        let Some(longest_prefix) = cycles.iter().max_by_key(|cycle| cycle.prefix) else {
+            return Ok(None);
+        };
+
+        // Before every ghost is inside its cycle, a synchronised step must be a
+        // prefix hit of the ghost with the longest prefix
+        if let Some(step) = longest_prefix
+            .prefix_hits
+            .iter()
+            .copied()
+            .filter(|step| *step > 0)
+            .find(|step| cycles.iter().all(|cycle| cycle.hits_at(*step)))
+        {
+            return Ok(Some(step));
+        }
+
+        let mut residues = vec![(0i128, 1i128)];
+        for cycle in &cycles {
+            residues = residues
+                .into_iter()
+                .flat_map(|(residue, modulus)| {
+                    cycle.cycle_hits.iter().filter_map(move |hit| {
+                        combine_congruences(
+                            (residue, modulus),
+                            (i128::from(*hit), i128::from(cycle.length)),
+                        )
+                    })
+                })
+                .collect();
+            residues.sort_unstable();
+            residues.dedup();
+        }
+
+        let lower_bound = i128::from(longest_prefix.prefix.max(1));
+        residues
+            .into_iter()
+            .map(|(residue, modulus)| lower_bound + (residue - lower_bound).rem_euclid(modulus))
+            .min()
+            .map(|step| {
+                u64::try_from(step).map_err(|err| format!("Synchronised step overflowed. '{err}'"))
+            })
+            .transpose()
+    }
+}
+
+/// Combines `x ≡ a (mod m)` and `x ≡ b (mod n)` into a single congruence
+/// modulo `lcm(m, n)`, if one exists
+fn combine_congruences((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let (gcd, p, _) = extended_gcd(m, n);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+    let lcm = m / gcd * n;
+    let k = ((b - a) / gcd % (n / gcd)) * p % (n / gcd);
+    Some(((a + m * k).rem_euclid(lcm), lcm))
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
@@ -1,6 +1,24 @@
 use std::io::Read;
 
+use common::{
+    extrapolation::NewtonPolynomial,
+    least_squares::{LeastSquaresFit, Prediction},
+};
+
+/// Highest degree tried when fitting noisy histories without a chosen degree
+const MAX_NOISY_DEGREE: usize = 20;
+
+/// Least squares fit of a single history
+#[derive(Debug)]
+struct NoisyExtrapolation {
+    degree: usize,
+    residuals: Vec<f64>,
+    forward: Prediction,
+    backward: Prediction,
+}
+
 fn main() -> Result<(), String> {
+    let noisy = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day09_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -14,6 +32,28 @@ fn main() -> Result<(), String> {
                     let part2 = extrapolate_histories_backward(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if let Some(degree) = noisy {
+                        let timer = std::time::Instant::now();
+                        let fits = extrapolate_noisy_histories(&input, degree)?;
+                        println!("{:?}:", timer.elapsed());
+                        for (line, fit) in fits.iter().enumerate() {
+                            let worst = fit
+                                .residuals
+                                .iter()
+                                .map(|residual| residual.abs())
+                                .fold(0., f64::max);
+                            println!(
+                                "{}: degree {} max residual {worst:.3} next {:.3} ± {:.3} previous {:.3} ± {:.3}",
+                                line + 1,
+                                fit.degree,
+                                fit.forward.value,
+                                fit.forward.standard_error,
+                                fit.backward.value,
+                                fit.backward.standard_error
+                            );
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,6 +63,25 @@ fn main() -> Result<(), String> {
     }
 }
 
+/// Parses `--noisy [DEGREE]`, requesting least squares extrapolation
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Option<usize>>, String> {
+    let mut noisy = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--noisy" => {
+                noisy = Some(
+                    args.next()
+                        .map(|degree| degree.parse())
+                        .transpose()
+                        .map_err(|err| format!("Failed to parse degree. '{err}'"))?,
+                );
+            }
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
+    }
+    Ok(noisy)
+}
+
 fn extrapolate_histories_forward(input: &str) -> Result<i64, String> {
     let histories = input
         .lines()
@@ -35,21 +94,9 @@ fn extrapolate_histories_forward(input: &str) -> Result<i64, String> {
 }
 
 fn extrapolate_history_forward(history: &[i64]) -> Result<i64, String> {
-    let diffs = std::iter::successors(Some(history.to_vec()), |prev| {
-        if prev.iter().all(|diff| diff.eq(&0)) {
-            None
-        } else {
-            Some(prev.windows(2).map(|wind| wind[1] - wind[0]).collect())
-        }
-    })
-    .collect::<Vec<_>>();
-    diffs
-        .into_iter()
-        .rev()
-        .try_fold(0, |prev_diff, cur_diffs| match cur_diffs.last() {
-            Some(cur_diff) => Ok(prev_diff + cur_diff),
-            None => Err("A list of differences was empty.".to_owned()),
-        })
+    NewtonPolynomial::from_history(history)?
+        .forward(1)
+        .and_then(narrow)
 }
 
 fn extrapolate_histories_backward(input: &str) -> Result<i64, String> {
@@ -64,21 +111,40 @@ fn extrapolate_histories_backward(input: &str) -> Result<i64, String> {
 }
 
 fn extrapolate_history_backward(history: &[i64]) -> Result<i64, String> {
-    let diffs = std::iter::successors(Some(history.to_vec()), |prev| {
-        if prev.iter().all(|diff| diff.eq(&0)) {
-            None
-        } else {
-            Some(prev.windows(2).map(|wind| wind[1] - wind[0]).collect())
-        }
-    })
-    .collect::<Vec<_>>();
-    diffs
-        .into_iter()
-        .rev()
-        .try_fold(0, |prev_diff, cur_diffs| match cur_diffs.first() {
-            Some(cur_diff) => Ok(cur_diff - prev_diff),
-            None => Err("A list of differences was empty.".to_owned()),
+    NewtonPolynomial::from_history(history)?
+        .backward(1)
+        .and_then(narrow)
+}
+
+fn narrow(value: i128) -> Result<i64, String> {
+    i64::try_from(value).map_err(|err| format!("Extrapolated value did not fit. '{err}'"))
+}
+
+/// Fits every history by least squares, with the given degree or, if `None`,
+/// the degree that best explains the history
+fn extrapolate_noisy_histories(
+    input: &str,
+    degree: Option<usize>,
+) -> Result<Vec<NoisyExtrapolation>, String> {
+    input
+        .lines()
+        .map(|line| {
+            let history = process_history(line)?
+                .into_iter()
+                .map(|value| value as f64)
+                .collect::<Vec<_>>();
+            let fit = match degree {
+                Some(degree) => LeastSquaresFit::fit(&history, degree)?,
+                None => LeastSquaresFit::fit_auto(&history, MAX_NOISY_DEGREE)?,
+            };
+            Ok(NoisyExtrapolation {
+                degree: fit.degree(),
+                residuals: fit.residuals().to_vec(),
+                forward: fit.forward(1),
+                backward: fit.backward(1),
+            })
         })
+        .collect()
 }
 
 fn process_history(line: &str) -> Result<Vec<i64>, String> {
@@ -103,4 +169,22 @@ mod tests {
     fn part2_test() {
         assert_eq!(super::extrapolate_histories_backward(PART1_INPUT1), Ok(2));
     }
+
+    #[test]
+    fn noisy_test() -> Result<(), String> {
+        // Second history is `1 3 6 10 15 21 28 36` with the 10 replaced by 12
+        const NOISY: &str = r"0 3 6 9 12 15 18 21
+1 3 6 12 15 21 28 36";
+
+        let fits = super::extrapolate_noisy_histories(NOISY, None)?;
+        assert_eq!(fits[0].degree, 1);
+        assert!((fits[0].forward.value - 24.).abs() < 1e-6);
+        assert_eq!(fits[1].degree, 2);
+        assert!((fits[1].forward.value - 45.).abs() < 2.);
+        assert!(fits[1].residuals[3] > 1.);
+
+        let fits = super::extrapolate_noisy_histories(NOISY, Some(1))?;
+        assert_eq!(fits[1].degree, 1);
+        Ok(())
+    }
 }
@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use crate::pipe_maze::{breadth_update, connections, enclosed_tiles, Loop, PipeMaze};
+
+/// A closed loop of pipes
+#[derive(Debug, PartialEq, Eq)]
+pub struct PipeLoop {
+    /// Tiles of the loop, in order
+    pub vertices: Vec<(usize, usize)>,
+    pub enclosed_area: u64,
+}
+
+/// Every closed loop and dangling pipe segment in a maze
+#[derive(Debug)]
+pub struct LoopAnalysis {
+    pub loops: Vec<PipeLoop>,
+    /// Connected pipes that do not close into a loop
+    pub dangling: Vec<Vec<(usize, usize)>>,
+    /// Loop each tile belongs to, indexed by `y * width + x`
+    loop_of_tile: Vec<Option<usize>>,
+    width: usize,
+}
+
+impl LoopAnalysis {
+    /// Walks the pipes from every tile not seen yet with [`breadth_update`],
+    /// and keeps the components where every pipe links to exactly two others
+    /// as loops
+    pub fn new(maze: &PipeMaze) -> Result<Self, String> {
+        let width = maze.width();
+        // Tiles with `S` replaced by its pipe, so it links like any other
+        let rows = (0..maze.height())
+            .map(|y| (0..width).map(|x| maze.tile((x, y))).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let pipes = rows.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let mut distances = vec![u64::MAX; width * maze.height()];
+        let mut loop_of_tile = vec![None; width * maze.height()];
+        let mut loops = vec![];
+        let mut dangling = vec![];
+
+        for y in 0..maze.height() {
+            for x in 0..width {
+                if distances[y * width + x] != u64::MAX || connections(pipes[y][x]).is_empty() {
+                    continue;
+                }
+                let mut component = vec![];
+                distances[y * width + x] = 0;
+                let mut breadth = VecDeque::from([(x, y)]);
+                while let Some(node) = breadth.pop_front() {
+                    component.push(node);
+                    breadth_update(&node, width, &pipes, &mut distances, &mut breadth)?;
+                }
+
+                if component
+                    .iter()
+                    .all(|tile| maze.linked_neighbours(*tile).len() == 2)
+                {
+                    let vertices = order_loop(maze, (x, y));
+                    for (x, y) in &vertices {
+                        loop_of_tile[y * width + x] = Some(loops.len());
+                    }
+                    loops.push(PipeLoop {
+                        enclosed_area: enclosed_tiles(&vertices),
+                        vertices,
+                    });
+                } else {
+                    component.sort_unstable_by_key(|(x, y)| (*y, *x));
+                    dangling.push(component);
+                }
+            }
+        }
+
+        Ok(Self {
+            loops,
+            dangling,
+            loop_of_tile,
+            width,
+        })
+    }
+
+    /// Loops that enclose `(x, y)`, from the innermost to the outermost
+    ///
+    /// Each loop that crosses row `y` classifies the row, and the tile is
+    /// enclosed by the loops that leave it [`Loop::Shadowed`].
+    pub fn enclosing_loops(
+        &self,
+        maze: &PipeMaze,
+        (x, y): (usize, usize),
+    ) -> Result<Vec<usize>, String> {
+        if x >= self.width || y >= maze.height() {
+            Err(format!("Tile {x},{y} is outside of the map."))?;
+        }
+        let row = &self.loop_of_tile[y * self.width..(y + 1) * self.width];
+        let mut crossing = row.iter().flatten().copied().collect::<Vec<_>>();
+        crossing.sort_unstable();
+        crossing.dedup();
+        let mut enclosing = crossing
+            .into_iter()
+            .filter(|pipe_loop| {
+                maze.classify_row(y, |x| row[x] == Some(*pipe_loop))[x] == Loop::Shadowed
+            })
+            .collect::<Vec<_>>();
+        enclosing.sort_by_key(|pipe_loop| self.loops[*pipe_loop].enclosed_area);
+        Ok(enclosing)
+    }
+}
+
+/// Walks a component where every tile is linked to exactly two others
+fn order_loop(maze: &PipeMaze, start: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut vertices = vec![start];
+    let mut previous = start;
+    let mut current = maze.linked_neighbours(start)[0];
+    while current != start {
+        vertices.push(current);
+        let next = maze
+            .linked_neighbours(current)
+            .into_iter()
+            .find(|next| *next != previous)
+            .unwrap_or(start);
+        previous = current;
+        current = next;
+    }
+    vertices
+}
@@ -1,24 +1,22 @@
-use std::{collections::VecDeque, io::Read};
-
-#[derive(Debug, Clone, Copy)]
-enum Loop {
-    Shadowed,
-    Outside,
-    StraightPipeLeftOutside,
-    StraightPipeRightOutside,
-    StraightPipeUpOutside,
-    StraightPipeDownOutside,
-    NEPipeOuter,
-    NEPipeInner,
-    NWPipeOuter,
-    NWPipeInner,
-    SEPipeOuter,
-    SEPipeInner,
-    SWPipeOuter,
-    SWPipeInner,
+mod loops;
+mod pipe_maze;
+mod render;
+
+use std::io::Read;
+
+use loops::LoopAnalysis;
+use pipe_maze::{breadth_update, PipeMaze};
+use render::{ImageFormat, Palette, RenderOptions};
+
+#[derive(Debug, Default)]
+struct Options {
+    render_to: Option<(String, RenderOptions)>,
+    loops: bool,
+    enclosing: Option<(usize, usize)>,
 }
 
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day10_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -32,6 +30,29 @@ fn main() -> Result<(), String> {
                     let part2 = enclosed_space_in_loop(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if options.loops || options.enclosing.is_some() {
+                        let maze = PipeMaze::new(&input)?;
+                        let analysis = LoopAnalysis::new(&maze)?;
+                        if options.loops {
+                            print_loop_analysis(&analysis);
+                        }
+                        if let Some(tile) = options.enclosing {
+                            println!(
+                                "{tile:?} is enclosed by loops {:?}",
+                                analysis.enclosing_loops(&maze, tile)?
+                            );
+                        }
+                    }
+
+                    if let Some((path, options)) = options.render_to {
+                        let maze = PipeMaze::new(&input)?;
+                        let distances = process_loop(&input)?;
+                        let file = std::fs::File::create(&path)
+                            .map_err(|err| format!("Failed to create '{path}'. '{err}'"))?;
+                        let mut writer = std::io::BufWriter::new(file);
+                        render::render(&maze, &distances, &options, &mut writer)?;
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -41,6 +62,73 @@ fn main() -> Result<(), String> {
     }
 }
 
+/// Parses `--render PATH` and its modifiers `--format <p6|png>`, `--scale N`,
+/// `--heat-map` and `--palette name=RRGGBB,...`, `--loops`, requesting every
+/// loop in the map, and `--enclosing X,Y`, requesting the loops around a tile
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut path = None;
+    let mut format = None;
+    let mut render = RenderOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--render" => path = Some(value()?),
+            "--format" => {
+                format = match value()?.as_str() {
+                    "p6" | "ppm" => Some(ImageFormat::P6),
+                    "png" => Some(ImageFormat::Png),
+                    other => Err(format!("Unknown image format. '{other}'"))?,
+                };
+            }
+            "--scale" => {
+                render.scale = value()?
+                    .parse()
+                    .map_err(|err| format!("Failed to parse scale. '{err}'"))?;
+            }
+            "--heat-map" => render.heat_map = true,
+            "--palette" => render.palette = Palette::default().with_overrides(&value()?)?,
+            "--loops" => options.loops = true,
+            "--enclosing" => {
+                let tile = value()?;
+                options.enclosing = tile
+                    .split_once(',')
+                    .and_then(|(x, y)| x.parse().ok().zip(y.parse().ok()))
+                    .map(Some)
+                    .ok_or(format!("Malformatted tile. '{tile}'"))?;
+            }
+            other => Err(format!("Unknown argument '{other}'."))?,
+        }
+    }
+    options.render_to = path.map(|path: String| {
+        render.format = format.unwrap_or(if path.ends_with(".png") {
+            ImageFormat::Png
+        } else {
+            ImageFormat::P6
+        });
+        (path, render)
+    });
+    Ok(options)
+}
+
+fn print_loop_analysis(analysis: &LoopAnalysis) {
+    for (i, pipe_loop) in analysis.loops.iter().enumerate() {
+        println!(
+            "Loop {i}: starts at {:?}, length {}, encloses {}",
+            pipe_loop.vertices[0],
+            pipe_loop.vertices.len(),
+            pipe_loop.enclosed_area
+        );
+    }
+    for segment in &analysis.dangling {
+        println!(
+            "Dangling segment: starts at {:?}, length {}",
+            segment[0],
+            segment.len()
+        );
+    }
+}
+
 fn steps_to_farthest_point_in_loop(input: &str) -> Result<u64, String> {
     process_loop(input)?
         .into_iter()
@@ -50,53 +138,7 @@ fn steps_to_farthest_point_in_loop(input: &str) -> Result<u64, String> {
 }
 
 fn enclosed_space_in_loop(input: &str) -> Result<u64, String> {
-    let lines = input.lines().map(str::as_bytes).collect::<Vec<_>>();
-    if let Some(start) = {
-        lines
-            .first()
-            .and_then(|first| {
-                first
-                    .iter()
-                    .position(|c| matches!(c, b'.' | b'|' | b'L' | b'J'))
-                    .map(|x| (x, 0))
-            })
-            .or_else(|| {
-                lines.last().and_then(|last| {
-                    last.iter()
-                        .position(|c| matches!(c, b'.' | b'|' | b'7' | b'F'))
-                        .map(|x| (x, lines.len() - 1))
-                })
-            })
-            .or_else(|| {
-                lines.iter().enumerate().find_map(|(y, line)| {
-                    line.first()
-                        .filter(|first| matches!(first, b'.' | b'-' | b'7' | b'J'))
-                        .map(|_| (0, y))
-                        .or_else(|| {
-                            line.last()
-                                .filter(|first| matches!(first, b'.' | b'-' | b'7' | b'J'))
-                                .map(|_| (line.len() - 1, y))
-                        })
-                })
-            })
-    } {
-        let line_length = lines[0].len();
-        let part_of_loop = process_loop(input)?;
-
-        let mut map = vec![Loop::Shadowed; lines.len() * lines[0].len()];
-        map[start.1 * line_length + start.0] = Loop::Outside;
-
-        // mark all outside as Loop::Outside
-        mark_all_outside(&start, line_length, &mut map, &lines, &part_of_loop)?;
-
-        // Count all that is still shadowed and is ground
-        Ok(map
-            .iter()
-            .filter(|tile| matches!(tile, Loop::Shadowed))
-            .count() as u64)
-    } else {
-        Err("Could not find a starting point at the edge of map.".to_owned())
-    }
+    PipeMaze::new(input)?.enclosed_area()
 }
 
 fn process_loop(input: &str) -> Result<Vec<u64>, String> {
@@ -123,594 +165,6 @@ fn process_loop(input: &str) -> Result<Vec<u64>, String> {
     }
 }
 
-fn get_up<'a>(node: &(usize, usize), _line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
-    if node.1 == 0 {
-        None
-    } else {
-        Some(&pipes[node.1 - 1][node.0])
-    }
-}
-
-fn get_down<'a>(node: &(usize, usize), line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
-    if node.1 + 1 == line_length {
-        None
-    } else {
-        Some(&pipes[node.1 + 1][node.0])
-    }
-}
-
-fn get_right<'a>(node: &(usize, usize), line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
-    if node.0 + 1 == line_length {
-        None
-    } else {
-        Some(&pipes[node.1][node.0 + 1])
-    }
-}
-
-fn get_left<'a>(node: &(usize, usize), _line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
-    if node.0 == 0 {
-        None
-    } else {
-        Some(&pipes[node.1][node.0 - 1])
-    }
-}
-
-fn breadth_update(
-    node: &(usize, usize),
-    line_length: usize,
-    pipes: &[&[u8]],
-    distances: &mut [u64],
-    search: &mut VecDeque<(usize, usize)>,
-) -> Result<(), String> {
-    let cur_dist = distances[node.1 * line_length + node.0];
-    let dirs = match pipes[node.1][node.0] {
-        b'S' => {
-            let mut dirs = vec![];
-            if matches!(get_up(node, line_length, pipes), Some(b'|' | b'7' | b'F')) {
-                dirs.push('U');
-            }
-            if matches!(get_down(node, line_length, pipes), Some(b'|' | b'J' | b'L')) {
-                dirs.push('D');
-            }
-            if matches!(get_left(node, line_length, pipes), Some(b'-' | b'L' | b'F')) {
-                dirs.push('L');
-            }
-            if matches!(
-                get_right(node, line_length, pipes),
-                Some(b'-' | b'7' | b'J')
-            ) {
-                dirs.push('R');
-            }
-            dirs
-        }
-        b'-' => {
-            vec!['R', 'L']
-        }
-        b'|' => {
-            vec!['U', 'D']
-        }
-        b'J' => {
-            vec!['U', 'L']
-        }
-        b'F' => {
-            vec!['R', 'D']
-        }
-        b'7' => {
-            vec!['L', 'D']
-        }
-        b'L' => {
-            vec!['U', 'R']
-        }
-        b'.' => {
-            vec![]
-        }
-        a => Err(format!("Unrecognized pipe. '{a}'"))?,
-    };
-    for dir in dirs {
-        match dir {
-            'U' => {
-                if let Some(subbed) = node.1.checked_sub(1) {
-                    if distances[subbed * line_length + node.0] == u64::MAX {
-                        search.push_back((node.0, subbed));
-                        distances[subbed * line_length + node.0] = cur_dist + 1;
-                    }
-                }
-            }
-            'D' => {
-                if let Some(added) = node.1.checked_add(1) {
-                    if added != pipes.len() && distances[added * line_length + node.0] == u64::MAX {
-                        search.push_back((node.0, added));
-                        distances[added * line_length + node.0] = cur_dist + 1;
-                    }
-                }
-            }
-            'L' => {
-                if let Some(subbed) = node.0.checked_sub(1) {
-                    if distances[node.1 * line_length + subbed] == u64::MAX {
-                        search.push_back((subbed, node.1));
-                        distances[node.1 * line_length + subbed] = cur_dist + 1;
-                    }
-                }
-            }
-            'R' => {
-                if let Some(added) = node.0.checked_add(1) {
-                    if added != line_length && distances[node.1 * line_length + added] == u64::MAX {
-                        search.push_back((added, node.1));
-                        distances[node.1 * line_length + added] = cur_dist + 1;
-                    }
-                }
-            }
-            _ => Err("Unrecognized direction.")?,
-        }
-    }
-    Ok(())
-}
-
-#[allow(clippy::too_many_lines)]
-fn mark_all_outside(
-    start: &(usize, usize),
-    line_length: usize,
-    map: &mut [Loop],
-    pipes: &[&[u8]],
-    part_of_loop: &[u64],
-) -> Result<(), String> {
-    let mut breadth = VecDeque::from_iter([*start]);
-
-    while let Some(node) = breadth.pop_front() {
-        let cur_tile = pipes[node.1][node.0];
-        let cur_tile_state = map[node.1 * line_length + node.0];
-
-        #[allow(clippy::match_same_arms)]
-        let dirs = match (cur_tile, cur_tile_state) {
-            (b'S', _) => vec![],
-            (b'.', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'-', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'-', Loop::StraightPipeUpOutside) => vec!['U', 'L', 'R'],
-            (b'-', Loop::StraightPipeDownOutside) => vec!['D', 'L', 'R'],
-            (b'|', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'|', Loop::StraightPipeLeftOutside) => vec!['U', 'D', 'L'],
-            (b'|', Loop::StraightPipeRightOutside) => vec!['U', 'D', 'R'],
-            (b'L', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'L', Loop::SWPipeOuter) => vec!['U', 'D', 'L', 'R'],
-            (b'L', Loop::SWPipeInner) => vec!['U', 'R'],
-            (b'F', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'F', Loop::NWPipeOuter) => vec!['U', 'D', 'L', 'R'],
-            (b'F', Loop::NWPipeInner) => vec!['D', 'R'],
-            (b'7', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'7', Loop::NEPipeOuter) => vec!['U', 'D', 'L', 'R'],
-            (b'7', Loop::NEPipeInner) => vec!['D', 'L'],
-            (b'J', Loop::Outside) => vec!['U', 'D', 'L', 'R'],
-            (b'J', Loop::SEPipeOuter) => vec!['U', 'D', 'L', 'R'],
-            (b'J', Loop::SEPipeInner) => vec!['U', 'L'],
-            (a, b) => Err(format!(
-                "Incorrect types. ({:?}, {b:?})",
-                char::from_u32(u32::from(a))
-            ))?,
-        };
-
-        for dir in dirs {
-            #[allow(clippy::match_same_arms)]
-            if match (dir, node) {
-                ('U', (_, 0)) => true,
-                ('D', (_, y)) if y + 1 == pipes.len() => true,
-                ('L', (0, _)) => true,
-                ('R', (x, _)) if x + 1 == line_length => true,
-                _ => false,
-            } {
-                continue;
-            }
-
-            let (dir_node, dir_tile, dir_tile_state, is_in_loop) = match dir {
-                'U' => (
-                    (node.0, node.1 - 1),
-                    pipes[node.1 - 1][node.0],
-                    &mut map[(node.1 - 1) * line_length + node.0],
-                    part_of_loop[(node.1 - 1) * line_length + node.0] != u64::MAX,
-                ),
-                'D' => (
-                    (node.0, node.1 + 1),
-                    pipes[node.1 + 1][node.0],
-                    &mut map[(node.1 + 1) * line_length + node.0],
-                    part_of_loop[(node.1 + 1) * line_length + node.0] != u64::MAX,
-                ),
-                'L' => (
-                    (node.0 - 1, node.1),
-                    pipes[node.1][node.0 - 1],
-                    &mut map[node.1 * line_length + node.0 - 1],
-                    part_of_loop[node.1 * line_length + node.0 - 1] != u64::MAX,
-                ),
-                'R' => (
-                    (node.0 + 1, node.1),
-                    pipes[node.1][node.0 + 1],
-                    &mut map[node.1 * line_length + node.0 + 1],
-                    part_of_loop[node.1 * line_length + node.0 + 1] != u64::MAX,
-                ),
-                _ => Err("Unknown direction.")?,
-            };
-
-            if !matches!(dir_tile_state, Loop::Shadowed) {
-                continue;
-            }
-
-            let dir_tile = match dir_tile {
-                b'S' => {
-                    #[allow(clippy::match_same_arms)]
-                    match (
-                        (dir_node.1 != 0).then_some(()).and_then(|()| {
-                            pipes
-                                .get(dir_node.1 - 1)
-                                .and_then(|line| line.get(dir_node.0))
-                        }),
-                        (dir_node.1 + 1 != pipes.len())
-                            .then_some(())
-                            .and_then(|()| {
-                                pipes
-                                    .get(dir_node.1 + 1)
-                                    .and_then(|line| line.get(dir_node.0))
-                            }),
-                        pipes
-                            .get(dir_node.1)
-                            .filter(|_| dir_node.0 != 0)
-                            .and_then(|line| line.get(dir_node.0 - 1)),
-                        pipes
-                            .get(dir_node.1)
-                            .filter(|_| dir_node.0 + 1 != line_length)
-                            .and_then(|line| line.get(dir_node.0 + 1)),
-                    ) {
-                        (Some(b'|' | b'7' | b'F'), Some(b'|' | b'L' | b'J'), _, _) => b'|',
-                        (Some(b'|' | b'7' | b'F'), _, Some(b'-' | b'F' | b'L'), _) => b'J',
-                        (Some(b'|' | b'7' | b'F'), _, _, Some(b'-' | b'7' | b'J')) => b'L',
-                        (_, Some(b'|' | b'L' | b'J'), Some(b'-' | b'F' | b'L'), _) => b'7',
-                        (_, Some(b'|' | b'L' | b'J'), _, Some(b'-' | b'7' | b'J')) => b'F',
-                        (_, _, Some(b'-' | b'F' | b'L'), Some(b'-' | b'7' | b'J')) => b'-',
-                        surroundings => Err(format!("Could not determinate S. {surroundings:?}"))?,
-                    }
-                }
-                else_ => else_,
-            };
-
-            #[allow(clippy::match_same_arms)]
-            match (dir, cur_tile, cur_tile_state, dir_tile, is_in_loop) {
-                // Going to a tile that is not part of the loop always flags as Outside
-                ('U' | 'D' | 'L' | 'R', _, _, _, false) => {
-                    *dir_tile_state = Loop::Outside;
-                }
-                // From Outside to is_in_loop
-                ('U', _, Loop::Outside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('D', _, Loop::Outside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('L', _, Loop::Outside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('R', _, Loop::Outside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('D' | 'R', _, Loop::Outside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('U' | 'R', _, Loop::Outside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('U' | 'L', _, Loop::Outside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('D' | 'L', _, Loop::Outside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                // From '-' to '-'
-                ('U', b'-', Loop::StraightPipeUpOutside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('L' | 'R', b'-', Loop::StraightPipeUpOutside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('D', b'-', Loop::StraightPipeDownOutside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('L' | 'R', b'-', Loop::StraightPipeDownOutside, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                // From '-' to '7'
-                ('R', b'-', Loop::StraightPipeUpOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('D', b'-', Loop::StraightPipeDownOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('R', b'-', Loop::StraightPipeDownOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                // From '-' to 'J'
-                ('U', b'-', Loop::StraightPipeUpOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('R', b'-', Loop::StraightPipeUpOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                ('R', b'-', Loop::StraightPipeDownOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                // From '-' to 'F'
-                ('L', b'-', Loop::StraightPipeUpOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('D', b'-', Loop::StraightPipeDownOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('L', b'-', Loop::StraightPipeDownOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                // From '-' to 'L'
-                ('U', b'-', Loop::StraightPipeUpOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('L', b'-', Loop::StraightPipeUpOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                ('L', b'-', Loop::StraightPipeDownOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                // From '|' to '|'
-                ('U' | 'D', b'|', Loop::StraightPipeLeftOutside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('L', b'|', Loop::StraightPipeLeftOutside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('U' | 'D', b'|', Loop::StraightPipeRightOutside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('R', b'|', Loop::StraightPipeRightOutside, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                // From '|' to '7'
-                ('U', b'|', Loop::StraightPipeLeftOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                ('L', b'|', Loop::StraightPipeLeftOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('U', b'|', Loop::StraightPipeRightOutside, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                // From '|' to 'J'
-                ('D', b'|', Loop::StraightPipeLeftOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                ('L', b'|', Loop::StraightPipeLeftOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('D', b'|', Loop::StraightPipeRightOutside, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                // From '|' to 'F'
-                ('U', b'|', Loop::StraightPipeLeftOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('U', b'|', Loop::StraightPipeRightOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                ('R', b'|', Loop::StraightPipeRightOutside, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                // From '|' to 'L'
-                ('D', b'|', Loop::StraightPipeLeftOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('D', b'|', Loop::StraightPipeRightOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                ('R', b'|', Loop::StraightPipeRightOutside, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                // From 'L' to '-'
-                ('R', b'L', Loop::SWPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('D', b'L', Loop::SWPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('R', b'L', Loop::SWPipeInner, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                // From 'L' to '|'
-                ('U', b'L', Loop::SWPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('L', b'L', Loop::SWPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('U', b'L', Loop::SWPipeInner, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                // From 'L' to 'J'
-                ('L' | 'R', b'L', Loop::SWPipeOuter, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('R', b'L', Loop::SWPipeInner, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                // From 'L' to 'F'
-                ('U' | 'D', b'L', Loop::SWPipeOuter, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('U', b'L', Loop::SWPipeInner, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                // From 'L' to '7'
-                ('U' | 'R', b'L', Loop::SWPipeOuter, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                ('D' | 'L', b'L', Loop::SWPipeOuter, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('U' | 'R', b'L', Loop::SWPipeInner, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('D' | 'L', b'L', Loop::SWPipeInner, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                // From 'F' to '-'
-                ('R', b'F', Loop::NWPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('U', b'F', Loop::NWPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('R', b'F', Loop::NWPipeInner, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                // From 'F' to '|'
-                ('D', b'F', Loop::NWPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('L', b'F', Loop::NWPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('D', b'F', Loop::NWPipeInner, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                // From 'F' to 'J'
-                ('U' | 'L', b'F', Loop::NWPipeOuter, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('D' | 'R', b'F', Loop::NWPipeOuter, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                ('U' | 'L', b'F', Loop::NWPipeInner, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                ('D' | 'R', b'F', Loop::NWPipeInner, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                // From 'F' to 'L'
-                ('U' | 'D', b'F', Loop::NWPipeOuter, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('D', b'F', Loop::NWPipeInner, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                // From 'F' to '7'
-                ('L' | 'R', b'F', Loop::NWPipeOuter, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('L' | 'R', b'F', Loop::NWPipeInner, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                // From '7' to '-'
-                ('U', b'7', Loop::NEPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('L', b'7', Loop::NEPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('L', b'7', Loop::NEPipeInner, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                // From '7' to '|'
-                ('D', b'7', Loop::NEPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('R', b'7', Loop::NEPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('D', b'7', Loop::NEPipeInner, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                // From '7' to 'J'
-                ('U' | 'D', b'7', Loop::NEPipeOuter, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeOuter;
-                }
-                ('U' | 'D', b'7', Loop::NEPipeInner, b'J', true) => {
-                    *dir_tile_state = Loop::SEPipeInner;
-                }
-                // From '7' to 'L'
-                ('U' | 'R', b'7', Loop::NEPipeOuter, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('D' | 'L', b'7', Loop::NEPipeOuter, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                ('U' | 'R', b'7', Loop::NEPipeInner, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                ('D' | 'L', b'7', Loop::NEPipeInner, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                // From '7' to 'F'
-                ('L' | 'R', b'7', Loop::NEPipeOuter, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('L' | 'R', b'7', Loop::NEPipeInner, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                // From 'J' to '-'
-                ('D', b'J', Loop::SEPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                ('L', b'J', Loop::SEPipeOuter, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeDownOutside;
-                }
-                ('L', b'J', Loop::SEPipeInner, b'-', true) => {
-                    *dir_tile_state = Loop::StraightPipeUpOutside;
-                }
-                // From 'J' to '|'
-                ('U', b'J', Loop::SEPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeRightOutside;
-                }
-                ('R', b'J', Loop::SEPipeOuter, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                ('U', b'J', Loop::SEPipeInner, b'|', true) => {
-                    *dir_tile_state = Loop::StraightPipeLeftOutside;
-                }
-                // From 'J' to '7'
-                ('U' | 'D', b'J', Loop::SEPipeOuter, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeOuter;
-                }
-                ('U' | 'D', b'J', Loop::SEPipeInner, b'7', true) => {
-                    *dir_tile_state = Loop::NEPipeInner;
-                }
-                // From 'J' to 'F'
-                ('U' | 'L', b'J', Loop::SEPipeOuter, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                ('D' | 'R', b'J', Loop::SEPipeOuter, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('U' | 'L', b'J', Loop::SEPipeInner, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeOuter;
-                }
-                ('D' | 'R', b'J', Loop::SEPipeInner, b'F', true) => {
-                    *dir_tile_state = Loop::NWPipeInner;
-                }
-                // From 'J' to 'L'
-                ('L' | 'R', b'J', Loop::SEPipeOuter, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeOuter;
-                }
-                ('L' | 'R', b'J', Loop::SEPipeInner, b'L', true) => {
-                    *dir_tile_state = Loop::SWPipeInner;
-                }
-                a => Err(format!("Unknown combination {a:?}."))?,
-            };
-
-            // If this is reached, the tile one the direction was updated
-            match dir {
-                'U' => breadth.push_back((node.0, node.1 - 1)),
-                'D' => breadth.push_back((node.0, node.1 + 1)),
-                'L' => breadth.push_back((node.0 - 1, node.1)),
-                'R' => breadth.push_back((node.0 + 1, node.1)),
-                _ => Err("Unknown direction.")?,
-            };
-        }
-    }
-
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -782,4 +236,99 @@ L7JLJL-JLJLJL--JLJ.L";
         assert_eq!(enclosed_space_in_loop(PART2_INPUT3), Ok(8));
         assert_eq!(enclosed_space_in_loop(PART2_INPUT4), Ok(10));
     }
+
+    #[test]
+    fn pipe_maze_test() -> Result<(), String> {
+        // The loop touches every edge of the map
+        const EDGES: &str = r"F-7F7
+|.LJ|
+S.F-J
+L-J..";
+
+        let maze = PipeMaze::new(EDGES)?;
+        assert_eq!(maze.tile((0, 2)), b'|');
+        let vertices = maze.main_loop()?;
+        assert_eq!(vertices.len(), 16);
+        assert_eq!(vertices[0], (0, 2));
+        assert_eq!(enclosed_space_in_loop(EDGES), Ok(2));
+
+        let maze = PipeMaze::new(PART1_INPUT2)?;
+        assert_eq!(maze.tile((0, 2)), b'F');
+        assert_eq!(maze.main_loop()?.len() / 2, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn loop_analysis_test() -> Result<(), String> {
+        // Main loop, a loop nested inside it, a disjoint loop and two dangling segments
+        const LOOPS: &str = r"S------7.F7
+|.F--7.|.LJ
+|.|..|.|...
+|.L--J.|-7.
+|......|..|
+L------J...";
+
+        let maze = PipeMaze::new(LOOPS)?;
+        let analysis = LoopAnalysis::new(&maze)?;
+        let summary = analysis
+            .loops
+            .iter()
+            .map(|pipe_loop| {
+                (
+                    pipe_loop.vertices[0],
+                    pipe_loop.vertices.len(),
+                    pipe_loop.enclosed_area,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(summary, [((0, 0), 24, 24), ((9, 0), 4, 0), ((2, 1), 10, 2)]);
+        let mut dangling = analysis.dangling.clone();
+        dangling.sort();
+        assert_eq!(dangling, [vec![(8, 3), (9, 3)], vec![(10, 4)]]);
+
+        assert_eq!(analysis.enclosing_loops(&maze, (3, 2))?, [2, 0]);
+        assert_eq!(analysis.enclosing_loops(&maze, (1, 1))?, [0]);
+        assert_eq!(analysis.enclosing_loops(&maze, (2, 1))?, [0]);
+        assert!(analysis.enclosing_loops(&maze, (10, 5))?.is_empty());
+        assert!(analysis.enclosing_loops(&maze, (11, 0)).is_err());
+        assert!(analysis.enclosing_loops(&maze, (0, 6)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn render_test() -> Result<(), String> {
+        let maze = PipeMaze::new(PART2_INPUT1)?;
+        let classification = maze.loop_classification()?;
+        assert_eq!(
+            classification
+                .iter()
+                .filter(|tile| **tile == pipe_maze::Loop::Shadowed)
+                .count(),
+            4
+        );
+        let distances = process_loop(PART2_INPUT1)?;
+        let options = RenderOptions {
+            scale: 1,
+            ..RenderOptions::default()
+        };
+        let mut ppm = vec![];
+        render::render(&maze, &distances, &options, &mut ppm)?;
+        assert!(ppm.starts_with(b"P6\n11 9\n255\n"));
+        assert_eq!(ppm.len(), 12 + 11 * 9 * 3);
+        // (2, 6) is enclosed by the loop
+        let inside = 12 + (6 * 11 + 2) * 3;
+        assert_eq!(ppm[inside..inside + 3], options.palette.inside);
+
+        let options = RenderOptions {
+            format: ImageFormat::Png,
+            heat_map: true,
+            scale: 4,
+            palette: Palette::default().with_overrides("inside=123456")?,
+        };
+        let mut png = vec![];
+        render::render(&maze, &distances, &options, &mut png)?;
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x2c\0\0\0\x24"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        Ok(())
+    }
 }
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+
+const PIPES: [u8; 6] = [b'|', b'-', b'L', b'J', b'7', b'F'];
+
+/// Where a tile sits relative to a loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loop {
+    /// Pipe of the loop itself
+    Pipe,
+    /// Enclosed by the loop, out of reach from the edge of the map
+    Shadowed,
+    Outside,
+}
+
+#[derive(Debug)]
+pub struct PipeMaze<'a> {
+    tiles: Vec<&'a [u8]>,
+    width: usize,
+    start: (usize, usize),
+    start_tile: u8,
+}
+
+impl<'a> PipeMaze<'a> {
+    pub fn new(input: &'a str) -> Result<Self, String> {
+        let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let width = tiles.first().map_or(0, |line| line.len());
+        if let Some(y) = tiles.iter().position(|line| line.len() != width) {
+            Err(format!("Line {} had a different length.", y + 1))?;
+        }
+        let start = tiles
+            .iter()
+            .enumerate()
+            .find_map(|(y, line)| line.iter().position(|c| c == &b'S').map(|x| (x, y)))
+            .ok_or("Failed to find starting position.".to_owned())?;
+
+        let mut maze = Self {
+            tiles,
+            width,
+            start,
+            start_tile: b'S',
+        };
+        let candidates = PIPES
+            .into_iter()
+            .filter(|pipe| {
+                connections(*pipe).iter().all(|dir| {
+                    maze.step(start, *dir)
+                        .is_some_and(|next| connections(maze.tile(next)).contains(&opposite(*dir)))
+                })
+            })
+            .collect::<Vec<_>>();
+        // When more than two neighbours connect to `S`, keep the pipe that closes a loop
+        for candidate in candidates {
+            maze.start_tile = candidate;
+            if maze.main_loop().is_ok() {
+                return Ok(maze);
+            }
+        }
+        Err("Could not determinate S.".to_owned())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    /// Tile at `position`, with `S` replaced by the pipe under it
+    pub fn tile(&self, position: (usize, usize)) -> u8 {
+        if position == self.start {
+            self.start_tile
+        } else {
+            self.tiles[position.1][position.0]
+        }
+    }
+
+    fn step(&self, (x, y): (usize, usize), dir: char) -> Option<(usize, usize)> {
+        match dir {
+            'U' => y.checked_sub(1).map(|y| (x, y)),
+            'D' => Some((x, y + 1)).filter(|(_, y)| *y < self.height()),
+            'L' => x.checked_sub(1).map(|x| (x, y)),
+            'R' => Some((x + 1, y)).filter(|(x, _)| *x < self.width),
+            _ => None,
+        }
+    }
+
+    /// Tiles of the loop through `S`, in order, starting at `S`
+    pub fn main_loop(&self) -> Result<Vec<(usize, usize)>, String> {
+        let mut vertices = vec![self.start];
+        let mut position = self.start;
+        let mut dir = connections(self.start_tile)[0];
+        loop {
+            let next = self
+                .step(position, dir)
+                .ok_or(format!("Loop left the map at {position:?}."))?;
+            if next == self.start {
+                return Ok(vertices);
+            }
+            let came_from = opposite(dir);
+            let next_connections = connections(self.tile(next));
+            if !next_connections.contains(&came_from) {
+                Err(format!("Loop is broken at {next:?}."))?;
+            }
+            dir = next_connections
+                .into_iter()
+                .find(|next_dir| *next_dir != came_from)
+                .ok_or(format!("Loop is broken at {next:?}."))?;
+            vertices.push(next);
+            position = next;
+        }
+    }
+
+    /// [`Loop`] classification of every tile against the main loop, indexed by
+    /// `y * width + x`
+    pub fn loop_classification(&self) -> Result<Vec<Loop>, String> {
+        let mut on_main_loop = vec![false; self.width * self.height()];
+        for (x, y) in self.main_loop()? {
+            on_main_loop[y * self.width + x] = true;
+        }
+        Ok((0..self.height())
+            .flat_map(|y| self.classify_row(y, |x| on_main_loop[y * self.width + x]))
+            .collect())
+    }
+
+    /// [`Loop`] classification of the tiles of row `y` against the loop made of
+    /// the tiles `x` for which `on_loop(x)` holds
+    ///
+    /// The row is scanned left to right, and every loop pipe with a northern
+    /// connection flips between outside and inside.
+    pub fn classify_row(&self, y: usize, on_loop: impl Fn(usize) -> bool) -> Vec<Loop> {
+        let mut inside = false;
+        (0..self.width)
+            .map(|x| {
+                if on_loop(x) {
+                    inside ^= connections(self.tile((x, y))).contains(&'U');
+                    Loop::Pipe
+                } else if inside {
+                    Loop::Shadowed
+                } else {
+                    Loop::Outside
+                }
+            })
+            .collect()
+    }
+
+    /// Number of tiles enclosed by the main loop
+    pub fn enclosed_area(&self) -> Result<u64, String> {
+        Ok(enclosed_tiles(&self.main_loop()?))
+    }
+
+    /// Neighbours that the pipe at `position` connects to and that connect back
+    pub fn linked_neighbours(&self, position: (usize, usize)) -> Vec<(usize, usize)> {
+        connections(self.tile(position))
+            .into_iter()
+            .filter_map(|dir| {
+                self.step(position, dir)
+                    .filter(|next| connections(self.tile(*next)).contains(&opposite(dir)))
+            })
+            .collect()
+    }
+}
+
+fn get_up<'a>(node: &(usize, usize), _line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
+    if node.1 == 0 {
+        None
+    } else {
+        Some(&pipes[node.1 - 1][node.0])
+    }
+}
+
+fn get_down<'a>(node: &(usize, usize), line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
+    if node.1 + 1 == line_length {
+        None
+    } else {
+        Some(&pipes[node.1 + 1][node.0])
+    }
+}
+
+fn get_right<'a>(node: &(usize, usize), line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
+    if node.0 + 1 == line_length {
+        None
+    } else {
+        Some(&pipes[node.1][node.0 + 1])
+    }
+}
+
+fn get_left<'a>(node: &(usize, usize), _line_length: usize, pipes: &'a [&[u8]]) -> Option<&'a u8> {
+    if node.0 == 0 {
+        None
+    } else {
+        Some(&pipes[node.1][node.0 - 1])
+    }
+}
+
+/// Visits the tiles that the pipe at `node` links to, giving the ones not seen
+/// yet the next distance and queueing them
+///
+/// Only neighbours whose pipe links back are followed.
+pub fn breadth_update(
+    node: &(usize, usize),
+    line_length: usize,
+    pipes: &[&[u8]],
+    distances: &mut [u64],
+    search: &mut VecDeque<(usize, usize)>,
+) -> Result<(), String> {
+    let cur_dist = distances[node.1 * line_length + node.0];
+    let dirs = match pipes[node.1][node.0] {
+        b'S' => {
+            let mut dirs = vec![];
+            if matches!(get_up(node, line_length, pipes), Some(b'|' | b'7' | b'F')) {
+                dirs.push('U');
+            }
+            if matches!(get_down(node, line_length, pipes), Some(b'|' | b'J' | b'L')) {
+                dirs.push('D');
+            }
+            if matches!(get_left(node, line_length, pipes), Some(b'-' | b'L' | b'F')) {
+                dirs.push('L');
+            }
+            if matches!(
+                get_right(node, line_length, pipes),
+                Some(b'-' | b'7' | b'J')
+            ) {
+                dirs.push('R');
+            }
+            dirs
+        }
+        b'-' => {
+            vec!['R', 'L']
+        }
+        b'|' => {
+            vec!['U', 'D']
+        }
+        b'J' => {
+            vec!['U', 'L']
+        }
+        b'F' => {
+            vec!['R', 'D']
+        }
+        b'7' => {
+            vec!['L', 'D']
+        }
+        b'L' => {
+            vec!['U', 'R']
+        }
+        b'.' => {
+            vec![]
+        }
+        a => Err(format!("Unrecognized pipe. '{a}'"))?,
+    };
+    for dir in dirs {
+        match dir {
+            'U' => {
+                if let Some(subbed) = node.1.checked_sub(1) {
+                    if distances[subbed * line_length + node.0] == u64::MAX
+                        && links_back(pipes[subbed][node.0], 'U')
+                    {
+                        search.push_back((node.0, subbed));
+                        distances[subbed * line_length + node.0] = cur_dist + 1;
+                    }
+                }
+            }
+            'D' => {
+                if let Some(added) = node.1.checked_add(1) {
+                    if added != pipes.len()
+                        && distances[added * line_length + node.0] == u64::MAX
+                        && links_back(pipes[added][node.0], 'D')
+                    {
+                        search.push_back((node.0, added));
+                        distances[added * line_length + node.0] = cur_dist + 1;
+                    }
+                }
+            }
+            'L' => {
+                if let Some(subbed) = node.0.checked_sub(1) {
+                    if distances[node.1 * line_length + subbed] == u64::MAX
+                        && links_back(pipes[node.1][subbed], 'L')
+                    {
+                        search.push_back((subbed, node.1));
+                        distances[node.1 * line_length + subbed] = cur_dist + 1;
+                    }
+                }
+            }
+            'R' => {
+                if let Some(added) = node.0.checked_add(1) {
+                    if added != line_length
+                        && distances[node.1 * line_length + added] == u64::MAX
+                        && links_back(pipes[node.1][added], 'R')
+                    {
+                        search.push_back((added, node.1));
+                        distances[node.1 * line_length + added] = cur_dist + 1;
+                    }
+                }
+            }
+            _ => Err("Unrecognized direction.")?,
+        }
+    }
+    Ok(())
+}
+
+/// Whether a step in `dir` onto `tile` is met by a pipe coming the other way,
+/// with `S` meeting every step
+fn links_back(tile: u8, dir: char) -> bool {
+    tile == b'S' || connections(tile).contains(&opposite(dir))
+}
+
+/// Number of tiles enclosed by a loop, from the shoelace formula and Pick's theorem
+pub fn enclosed_tiles(vertices: &[(usize, usize)]) -> u64 {
+    let twice_area = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(lhs, rhs)| (lhs.0 * rhs.1) as i64 - (rhs.0 * lhs.1) as i64)
+        .sum::<i64>()
+        .unsigned_abs();
+    // A = i + b/2 - 1
+    (twice_area + 2 - vertices.len() as u64) / 2
+}
+
+/// Directions a pipe connects to
+pub fn connections(tile: u8) -> Vec<char> {
+    match tile {
+        b'|' => vec!['U', 'D'],
+        b'-' => vec!['L', 'R'],
+        b'L' => vec!['U', 'R'],
+        b'J' => vec!['U', 'L'],
+        b'7' => vec!['D', 'L'],
+        b'F' => vec!['D', 'R'],
+        _ => vec![],
+    }
+}
+
+fn opposite(dir: char) -> char {
+    match dir {
+        'U' => 'D',
+        'D' => 'U',
+        'L' => 'R',
+        _ => 'L',
+    }
+}
@@ -0,0 +1,240 @@
+use std::io::Write;
+
+use common::ppm;
+
+use crate::pipe_maze::{connections, Loop, PipeMaze};
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary portable pixmap
+    P6,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub start: Rgb,
+    pub main_loop: Rgb,
+    pub inside: Rgb,
+    pub outside: Rgb,
+    /// Pipes that are not part of the main loop
+    pub stray_pipe: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            start: [0, 0, 255],
+            main_loop: [255, 255, 255],
+            inside: [0, 160, 0],
+            outside: [40, 0, 0],
+            stray_pipe: [110, 60, 60],
+        }
+    }
+}
+
+impl Palette {
+    /// Overrides colours from a comma separated list of `name=RRGGBB`
+    pub fn with_overrides(mut self, overrides: &str) -> Result<Self, String> {
+        for entry in overrides.split(',').filter(|entry| !entry.is_empty()) {
+            let (name, hex) = entry
+                .split_once('=')
+                .ok_or(format!("Malformatted colour. '{entry}'"))?;
+            let colour = parse_hex_colour(hex)?;
+            match name {
+                "start" => self.start = colour,
+                "loop" => self.main_loop = colour,
+                "inside" => self.inside = colour,
+                "outside" => self.outside = colour,
+                "stray" => self.stray_pipe = colour,
+                _ => Err(format!("Unknown palette entry. '{name}'"))?,
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn parse_hex_colour(hex: &str) -> Result<Rgb, String> {
+    let value = u32::from_str_radix(hex.trim_start_matches('#'), 16)
+        .ok()
+        .filter(|_| hex.trim_start_matches('#').len() == 6)
+        .ok_or(format!("Malformatted colour. '{hex}'"))?;
+    let [_, r, g, b] = value.to_be_bytes();
+    Ok([r, g, b])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub format: ImageFormat,
+    pub palette: Palette,
+    /// Width and height, in pixels, of each tile
+    pub scale: usize,
+    /// Colour the main loop by its distance from `S`
+    pub heat_map: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            format: ImageFormat::P6,
+            palette: Palette::default(),
+            scale: 3,
+            heat_map: false,
+        }
+    }
+}
+
+/// Renders the maze into `writer`
+///
+/// `distances` are the BFS distances from `S`, indexed by `y * width + x`, and
+/// are only used when drawing a heat map.
+pub fn render(
+    maze: &PipeMaze,
+    distances: &[u64],
+    options: &RenderOptions,
+    writer: &mut impl Write,
+) -> Result<(), String> {
+    if options.scale == 0 {
+        Err("Scale must be at least 1.")?;
+    }
+    let classification = maze.loop_classification()?;
+    let farthest = distances
+        .iter()
+        .filter(|distance| **distance != u64::MAX)
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+
+    let scale = options.scale;
+    let (width, height) = (maze.width() * scale, maze.height() * scale);
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for py in 0..height {
+        for px in 0..width {
+            let (x, y) = (px / scale, py / scale);
+            let tile = maze.tile((x, y));
+            let tile_loop = classification[y * maze.width() + x];
+            let pipe_colour = match tile_loop {
+                _ if (x, y) == maze.start() => options.palette.start,
+                Loop::Pipe if options.heat_map => heat(distances[y * maze.width() + x], farthest),
+                Loop::Pipe => options.palette.main_loop,
+                Loop::Shadowed | Loop::Outside => options.palette.stray_pipe,
+            };
+            let background = match tile_loop {
+                Loop::Shadowed => options.palette.inside,
+                Loop::Pipe | Loop::Outside => options.palette.outside,
+            };
+            let colour = if on_pipe(tile, px % scale, py % scale, scale) {
+                pipe_colour
+            } else {
+                background
+            };
+            pixels.extend_from_slice(&colour);
+        }
+    }
+
+    match options.format {
+        ImageFormat::P6 => ppm::write_p6(writer, width, height, &pixels),
+        ImageFormat::Png => write_png(writer, width, height, &pixels),
+    }
+    .map_err(|err| format!("Failed to write image. '{err}'"))
+}
+
+/// Whether the pixel at `(px, py)`, inside a tile, is covered by the pipe drawn on it
+fn on_pipe(tile: u8, px: usize, py: usize, scale: usize) -> bool {
+    let dirs = connections(tile);
+    if dirs.is_empty() {
+        return false;
+    }
+    if scale < 3 {
+        return true;
+    }
+    let band = (scale / 3)..(scale - scale / 3);
+    match (band.contains(&px), band.contains(&py)) {
+        (true, true) => true,
+        (true, false) => dirs.contains(if py < band.start { &'U' } else { &'D' }),
+        (false, true) => dirs.contains(if px < band.start { &'L' } else { &'R' }),
+        (false, false) => false,
+    }
+}
+
+/// Blue for tiles close to `S`, red for the farthest ones
+fn heat(distance: u64, farthest: u64) -> Rgb {
+    let hot = u8::try_from(distance.min(farthest) * 255 / farthest).unwrap_or(u8::MAX);
+    [hot, 0, u8::MAX - hot]
+}
+
+fn write_png(
+    writer: &mut impl Write,
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+) -> std::io::Result<()> {
+    let to_u32 = |value: usize| {
+        u32::try_from(value)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))
+    };
+
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&to_u32(width)?.to_be_bytes());
+    header.extend_from_slice(&to_u32(height)?.to_be_bytes());
+    // 8 bit depth, truecolour, deflate, no filter, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_png_chunk(writer, b"IHDR", &header)?;
+
+    let scanlines = pixels
+        .chunks(width * 3)
+        .flat_map(|line| std::iter::once(0).chain(line.iter().copied()))
+        .collect::<Vec<_>>();
+    write_png_chunk(writer, b"IDAT", &zlib_stored(&scanlines))?;
+    write_png_chunk(writer, b"IEND", &[])
+}
+
+fn write_png_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(data.len())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(usize::from(u16::MAX)).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = u8::from(blocks.peek().is_none());
+        let len = u16::try_from(block.len()).unwrap_or(u16::MAX);
+        stream.push(last);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + u32::from(*byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    stream
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    !bytes.fold(u32::MAX, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
@@ -1,6 +1,22 @@
+mod universe;
+
 use std::io::Read;
 
+use universe::{Expansion, Universe};
+
+#[derive(Debug, Default)]
+struct Queries {
+    expansion: Option<Expansion>,
+    nearest: Option<usize>,
+    farthest: bool,
+    histogram: Option<u64>,
+    empty: bool,
+    to_expanded: Option<(usize, usize)>,
+    to_original: Option<(u64, u64)>,
+}
+
 fn main() -> Result<(), String> {
+    let queries = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day11_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -14,7 +30,7 @@ fn main() -> Result<(), String> {
                     let part2 = find_paths_between_galaxies(&input, 1_000_000);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
-                    Ok(())
+                    run_queries(&input, &queries)
                 }
                 Err(err) => Err(err.to_string()),
             }
@@ -23,89 +39,128 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn find_paths_between_galaxies(input: &str, rate_of_expansion: usize) -> Result<u64, String> {
-    let make_pairs = |v: &[(usize, usize)]| -> Vec<((usize, usize), (usize, usize))> {
-        match v {
-            [source, tail @ ..] => tail.iter().map(|dest| (*source, *dest)).collect(),
-            [] => vec![],
+/// Parses `--expansion COLUMNS,ROWS`, the rates used by the queries `--nearest INDEX`,
+/// `--farthest`, `--histogram BUCKET_WIDTH`, `--empty`, `--to-expanded X,Y` and
+/// `--to-original X,Y`
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Queries, String> {
+    let mut queries = Queries::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--expansion" => {
+                let rates = value()?;
+                let (columns, rows) = rates
+                    .split_once(',')
+                    .and_then(|(columns, rows)| columns.parse().ok().zip(rows.parse().ok()))
+                    .ok_or(format!("Malformatted expansion. '{rates}'"))?;
+                queries.expansion = Some(Expansion::new(columns, rows)?);
+            }
+            "--nearest" => {
+                queries.nearest = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse galaxy index. '{err}'"))?,
+                );
+            }
+            "--farthest" => queries.farthest = true,
+            "--empty" => queries.empty = true,
+            "--to-expanded" => queries.to_expanded = Some(parse_pair(&value()?)?),
+            "--to-original" => queries.to_original = Some(parse_pair(&value()?)?),
+            "--histogram" => {
+                queries.histogram = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse bucket width. '{err}'"))?,
+                );
+            }
+            other => return Err(format!("Unknown argument '{other}'.")),
         }
-    };
-    let expanded_universe = get_expanded_column_rows(input)?;
-    let galaxy_coordinates = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter_map(|(x, c)| if c == '#' { Some((x, y)) } else { None })
-                .map(|(x, y)| {
-                    (
-                        expanded_universe
-                            .0
-                            .iter()
-                            .filter(|expanded_x| expanded_x < &&x)
-                            .count()
-                            * (rate_of_expansion - 1)
-                            + x,
-                        y,
-                    )
-                })
-                .collect::<Vec<_>>()
-        })
-        .map(|(x, y)| {
-            (
-                x,
-                expanded_universe
-                    .1
-                    .iter()
-                    .filter(|expanded_y| expanded_y < &&y)
-                    .count()
-                    * (rate_of_expansion - 1)
-                    + y,
-            )
-        })
-        .collect::<Vec<_>>();
-    let galaxy_pairs = std::iter::successors(Some(galaxy_coordinates.as_slice()), |slice| {
-        if slice.len() > 1 {
-            Some(&slice[1..])
-        } else {
-            None
+    }
+    Ok(queries)
+}
+
+fn parse_pair<T: std::str::FromStr>(pair: &str) -> Result<(T, T), String> {
+    pair.split_once(',')
+        .and_then(|(x, y)| x.trim().parse().ok().zip(y.trim().parse().ok()))
+        .ok_or(format!("Malformatted coordinate. '{pair}'"))
+}
+
+fn run_queries(input: &str, queries: &Queries) -> Result<(), String> {
+    let universe = read_universe(input)?;
+    let expansion = queries
+        .expansion
+        .map_or_else(|| Expansion::uniform(2), Ok)?;
+    if let Some(index) = queries.nearest {
+        match universe.nearest_galaxy(index, expansion)? {
+            Some((nearest, distance)) => {
+                println!("Nearest to galaxy {index}: galaxy {nearest} at {distance}");
+            }
+            None => println!("Galaxy {index} is alone."),
         }
-    })
-    .flat_map(make_pairs)
-    .collect::<Vec<_>>();
-    galaxy_pairs
-        .into_iter()
-        .map(|(source, dest)| source.0.abs_diff(dest.0) + source.1.abs_diff(dest.1))
-        .map(|dist| {
-            u64::try_from(dist)
-                .map_err(|err| format!("Failed to convert from usize to u64. '{err}'"))
-        })
-        .sum()
+    }
+    if queries.farthest {
+        if let Some((lhs, rhs, distance)) = universe.farthest_pair(expansion)? {
+            println!("Farthest pair: galaxies {lhs} and {rhs} at {distance}");
+        }
+    }
+    if queries.empty {
+        let (columns, rows) = universe.empty_lines();
+        println!("Empty columns: {columns:?}");
+        println!("Empty rows: {rows:?}");
+    }
+    if let Some(original) = queries.to_expanded {
+        println!(
+            "{original:?} expands to {:?}",
+            universe.to_expanded(original, expansion)?
+        );
+    }
+    if let Some(expanded) = queries.to_original {
+        println!(
+            "{expanded:?} comes from {:?}",
+            universe.to_original(expanded, expansion)?
+        );
+    }
+    if let Some(bucket_width) = queries.histogram {
+        for (bucket, count) in universe.distance_histogram(expansion, bucket_width)? {
+            println!("{bucket}..{}: {count}", bucket + bucket_width);
+        }
+    }
+    Ok(())
 }
 
-fn get_expanded_column_rows(input: &str) -> Result<(Vec<usize>, Vec<usize>), String> {
-    let line_length = input.lines().next().ok_or("Empty imput")?.len();
-    let rows = input
+/// Reads either a grid of `.` and `#`, or a sparse list of `x,y` galaxy coordinates
+fn read_universe(input: &str) -> Result<Universe, String> {
+    let is_sparse = input
         .lines()
-        .enumerate()
-        .filter_map(|(y, line)| {
-            if line.chars().all(|c| c == '.') {
-                Some(y)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    let columns = (0..line_length)
-        .filter(|col| {
-            input
-                .lines()
-                .all(|line| line.chars().nth(*col).filter(|c| c == &'.').is_some())
-        })
-        .collect();
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.contains(','));
+    let galaxies = if is_sparse {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_pair)
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        input
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    };
+    Ok(Universe::new(galaxies))
+}
 
-    Ok((columns, rows))
+fn find_paths_between_galaxies(input: &str, rate_of_expansion: u64) -> Result<u64, String> {
+    read_universe(input)?
+        .sum_of_distances(Expansion::uniform(rate_of_expansion)?)
+        .and_then(|sum| {
+            u64::try_from(sum).map_err(|err| format!("Failed to convert from u128 to u64. '{err}'"))
+        })
 }
 
 #[cfg(test)]
@@ -126,7 +181,7 @@ mod tests {
     #[test]
     fn part1_test() {
         assert_eq!(
-            get_expanded_column_rows(PART1_INPUT1),
+            read_universe(PART1_INPUT1).map(|universe| universe.empty_lines()),
             Ok((vec![2, 5, 8], vec![3, 7]))
         );
         assert_eq!(find_paths_between_galaxies(PART1_INPUT1, 2), Ok(374));
@@ -137,4 +192,88 @@ mod tests {
         assert_eq!(find_paths_between_galaxies(PART1_INPUT1, 10), Ok(1030));
         assert_eq!(find_paths_between_galaxies(PART1_INPUT1, 100), Ok(8410));
     }
+
+    #[test]
+    fn sparse_universe_test() -> Result<(), String> {
+        const SPARSE: &str = r"3,0
+7,1
+0,2
+6,4
+1,5
+9,6
+7,8
+0,9
+4,9";
+        assert_eq!(read_universe(SPARSE), read_universe(PART1_INPUT1));
+
+        let universe = read_universe("0,0\n1000000000,1000000000")?;
+        assert!(Expansion::new(0, 2).is_err());
+        assert!(Expansion::uniform(0).is_err());
+        let expansion = Expansion::new(2, 1_000_000)?;
+        let far = universe.to_expanded((1_000_000_000, 1_000_000_000), expansion)?;
+        assert_eq!(far, (1_999_999_999, 999_999_999_000_001));
+        assert_eq!(
+            universe.to_original(far, expansion),
+            Ok((1_000_000_000, 1_000_000_000))
+        );
+        // Inside the expanded empty row right after the first galaxy
+        assert_eq!(universe.to_original((0, 999_999), expansion), Ok((0, 1)));
+        assert_eq!(universe.to_original((0, 1_000_001), expansion), Ok((0, 2)));
+        assert_eq!(
+            universe.sum_of_distances(expansion),
+            Ok(u128::from(far.0 + far.1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn universe_test() -> Result<(), String> {
+        let universe = read_universe(PART1_INPUT1)?;
+        let pair_distances = |expansion| -> Result<Vec<(usize, usize, u64)>, String> {
+            let galaxies = universe.expanded_galaxies(expansion)?;
+            Ok(galaxies
+                .iter()
+                .enumerate()
+                .flat_map(|(i, lhs)| {
+                    galaxies
+                        .iter()
+                        .enumerate()
+                        .skip(i + 1)
+                        .map(move |(j, rhs)| (i, j, lhs.0.abs_diff(rhs.0) + lhs.1.abs_diff(rhs.1)))
+                })
+                .collect())
+        };
+
+        let expansion = Expansion::uniform(2)?;
+        let distances = pair_distances(expansion)?;
+        assert_eq!(universe.nearest_galaxy(4, expansion), Ok(Some((2, 5))));
+        assert_eq!(
+            universe
+                .farthest_pair(expansion)?
+                .map(|(.., distance)| distance),
+            distances.iter().map(|(.., distance)| *distance).max()
+        );
+
+        let histogram = universe.distance_histogram(expansion, 1)?;
+        assert!(universe.distance_histogram(expansion, 0).is_err());
+        assert_eq!(histogram.values().sum::<u64>(), 36);
+        assert_eq!(
+            histogram
+                .iter()
+                .map(|(distance, count)| distance * count)
+                .sum::<u64>(),
+            374
+        );
+
+        // Only the columns expand
+        let columns_only = Expansion::new(10, 1)?;
+        assert_eq!(
+            universe.sum_of_distances(columns_only),
+            Ok(pair_distances(columns_only)?
+                .iter()
+                .map(|(.., distance)| u128::from(*distance))
+                .sum())
+        );
+        Ok(())
+    }
 }
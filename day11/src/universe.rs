@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+
+/// How many times larger each empty column and row becomes
+///
+/// Rates are at least 1, so every line keeps a width and coordinates map both
+/// ways.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expansion {
+    columns: u64,
+    rows: u64,
+}
+
+impl Expansion {
+    pub fn new(columns: u64, rows: u64) -> Result<Self, String> {
+        if columns == 0 || rows == 0 {
+            Err("Empty lines vanish when the expansion rate is 0.")?;
+        }
+        Ok(Self { columns, rows })
+    }
+
+    pub fn uniform(rate: u64) -> Result<Self, String> {
+        Self::new(rate, rate)
+    }
+}
+
+/// Galaxies, and the columns and rows that contain at least one of them
+///
+/// Only occupied lines are stored, so a sparse universe costs as much as its
+/// galaxies regardless of how far apart they are. Every other line is empty.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Universe {
+    /// Galaxies in the original, unexpanded, coordinates
+    galaxies: Vec<(usize, usize)>,
+    occupied_columns: Vec<usize>,
+    occupied_rows: Vec<usize>,
+}
+
+impl Universe {
+    pub fn new(galaxies: Vec<(usize, usize)>) -> Self {
+        let occupied = |axis: fn(&(usize, usize)) -> usize| {
+            let mut lines = galaxies.iter().map(axis).collect::<Vec<_>>();
+            lines.sort_unstable();
+            lines.dedup();
+            lines
+        };
+        Self {
+            occupied_columns: occupied(|galaxy| galaxy.0),
+            occupied_rows: occupied(|galaxy| galaxy.1),
+            galaxies,
+        }
+    }
+
+    /// Empty columns and rows up to the farthest galaxy
+    pub fn empty_lines(&self) -> (Vec<usize>, Vec<usize>) {
+        let empty = |occupied: &[usize]| {
+            occupied
+                .last()
+                .map(|last| {
+                    (0..*last)
+                        .filter(|line| occupied.binary_search(line).is_err())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        (empty(&self.occupied_columns), empty(&self.occupied_rows))
+    }
+
+    /// Position of an original coordinate after the universe expands
+    pub fn to_expanded(
+        &self,
+        (x, y): (usize, usize),
+        expansion: Expansion,
+    ) -> Result<(u64, u64), String> {
+        Ok((
+            expand(x, &self.occupied_columns, expansion.columns)?,
+            expand(y, &self.occupied_rows, expansion.rows)?,
+        ))
+    }
+
+    /// Original coordinate of a position in the expanded universe
+    ///
+    /// Positions inside an expanded empty line map back to that line.
+    pub fn to_original(
+        &self,
+        (x, y): (u64, u64),
+        expansion: Expansion,
+    ) -> Result<(usize, usize), String> {
+        Ok((
+            contract(x, &self.occupied_columns, expansion.columns)?,
+            contract(y, &self.occupied_rows, expansion.rows)?,
+        ))
+    }
+
+    /// Galaxies in the expanded coordinates, in the same order they were given
+    pub fn expanded_galaxies(&self, expansion: Expansion) -> Result<Vec<(u64, u64)>, String> {
+        self.galaxies
+            .iter()
+            .map(|galaxy| self.to_expanded(*galaxy, expansion))
+            .collect()
+    }
+
+    /// Sum of the distances between every pair of galaxies
+    ///
+    /// The Manhattan distance splits into independent axes, and on a sorted axis
+    /// the `i`-th coordinate contributes `i * x_i - (x_0 + ... + x_{i-1})`.
+    pub fn sum_of_distances(&self, expansion: Expansion) -> Result<u128, String> {
+        let (mut xs, mut ys) = self
+            .expanded_galaxies(expansion)?
+            .into_iter()
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+        xs.sort_unstable();
+        ys.sort_unstable();
+        Ok(axis_sum_of_distances(&xs) + axis_sum_of_distances(&ys))
+    }
+
+    /// Closest other galaxy to the galaxy at `index`, and its distance
+    pub fn nearest_galaxy(
+        &self,
+        index: usize,
+        expansion: Expansion,
+    ) -> Result<Option<(usize, u64)>, String> {
+        let galaxies = self.expanded_galaxies(expansion)?;
+        let origin = *galaxies
+            .get(index)
+            .ok_or(format!("Galaxy {index} does not exist."))?;
+        Ok(galaxies
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(other, galaxy)| (other, distance(origin, *galaxy)))
+            .min_by_key(|(_, distance)| *distance))
+    }
+
+    /// Pair of galaxies that are farthest apart, and their distance
+    ///
+    /// The Manhattan distance is the largest spread of either `x + y` or `x - y`.
+    pub fn farthest_pair(
+        &self,
+        expansion: Expansion,
+    ) -> Result<Option<(usize, usize, u64)>, String> {
+        let galaxies = self.expanded_galaxies(expansion)?;
+        let extremes = |key: &dyn Fn(&(u64, u64)) -> i128| {
+            let min = galaxies
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, galaxy)| key(galaxy))
+                .map(|(index, _)| index)?;
+            let max = galaxies
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, galaxy)| key(galaxy))
+                .map(|(index, _)| index)?;
+            Some((min, max, distance(galaxies[min], galaxies[max])))
+        };
+        let sum = extremes(&|(x, y)| i128::from(*x) + i128::from(*y));
+        let difference = extremes(&|(x, y)| i128::from(*x) - i128::from(*y));
+        Ok(sum
+            .into_iter()
+            .chain(difference)
+            .filter(|(lhs, rhs, _)| lhs != rhs)
+            .max_by_key(|(_, _, distance)| *distance))
+    }
+
+    /// Number of pairs of galaxies for each range of `bucket_width` distances,
+    /// keyed by the start of the range
+    ///
+    /// Every pair is visited, so unlike [`Universe::sum_of_distances`] this
+    /// takes time quadratic on the number of galaxies, which is fine for a
+    /// puzzle input but not for universes with hundreds of thousands of them.
+    pub fn distance_histogram(
+        &self,
+        expansion: Expansion,
+        bucket_width: u64,
+    ) -> Result<BTreeMap<u64, u64>, String> {
+        if bucket_width == 0 {
+            Err("Bucket width must be at least 1.")?;
+        }
+        let galaxies = self.expanded_galaxies(expansion)?;
+        let mut histogram = BTreeMap::new();
+        for (i, source) in galaxies.iter().enumerate() {
+            for dest in &galaxies[(i + 1)..] {
+                let bucket = distance(*source, *dest) / bucket_width * bucket_width;
+                *histogram.entry(bucket).or_insert(0) += 1;
+            }
+        }
+        Ok(histogram)
+    }
+}
+
+fn expand(coordinate: usize, occupied: &[usize], rate: u64) -> Result<u64, String> {
+    let empty_before = (coordinate - occupied.partition_point(|line| *line < coordinate)) as u64;
+    empty_before
+        .checked_mul(rate - 1)
+        .and_then(|growth| growth.checked_add(coordinate as u64))
+        .ok_or("Expanded coordinate overflowed.".to_owned())
+}
+
+/// Inverse of [`expand`], the last original line that starts at or before `expanded`
+fn contract(expanded: u64, occupied: &[usize], rate: u64) -> Result<usize, String> {
+    // Expanding never moves a line backwards, so the original line is at most `expanded`
+    let (mut low, mut high) = (
+        0usize,
+        usize::try_from(expanded).map_err(|err| err.to_string())?,
+    );
+    while low < high {
+        let middle = low + (high - low).div_ceil(2);
+        // An overflowing line is certainly past `expanded`
+        if expand(middle, occupied, rate).is_ok_and(|line| line <= expanded) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn distance(lhs: (u64, u64), rhs: (u64, u64)) -> u64 {
+    lhs.0.abs_diff(rhs.0) + lhs.1.abs_diff(rhs.1)
+}
+
+fn axis_sum_of_distances(sorted: &[u64]) -> u128 {
+    sorted
+        .iter()
+        .zip(0u128..)
+        .fold((0u128, 0u128), |(sum, prefix), (coordinate, i)| {
+            let coordinate = u128::from(*coordinate);
+            (sum + coordinate * i - prefix, prefix + coordinate)
+        })
+        .0
+}
@@ -0,0 +1,231 @@
+use crate::big_count::BigCount;
+
+/// What every arrangement of a row agrees on for a single spring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deduction {
+    Damaged,
+    Operational,
+    Undetermined,
+}
+
+/// Row of springs together with the sizes of its groups of damaged springs
+///
+/// The states are padded with a trailing `.`, so every group is followed by an
+/// operational spring, and `suffix` counts, for each `(position, group)`, the
+/// ways to place the remaining groups from that position onwards. Counting,
+/// enumerating, sampling and deducing all walk this one table.
+#[derive(Debug)]
+pub struct SpringRow {
+    states: Vec<u8>,
+    groups: Vec<usize>,
+    /// Position of the first `.` at or after each position
+    next_operational: Vec<usize>,
+    suffix: Vec<Vec<BigCount>>,
+}
+
+impl SpringRow {
+    pub fn new(states: &str, groups: &[usize]) -> Result<Self, String> {
+        if let Some(invalid) = states.chars().find(|s| !matches!(s, '.' | '#' | '?')) {
+            Err(format!("Invalid state '{invalid}'."))?;
+        }
+        let mut states = states.as_bytes().to_vec();
+        states.push(b'.');
+        let len = states.len();
+
+        let mut next_operational = vec![len; len + 1];
+        for position in (0..len).rev() {
+            next_operational[position] = if states[position] == b'.' {
+                position
+            } else {
+                next_operational[position + 1]
+            };
+        }
+        // Springs needed by the groups from each one onwards, with their padding
+        let mut needed = vec![0; groups.len() + 1];
+        for group in (0..groups.len()).rev() {
+            needed[group] = needed[group + 1] + groups[group] + 1;
+        }
+
+        let mut row = Self {
+            states,
+            groups: groups.to_vec(),
+            next_operational,
+            suffix: vec![vec![BigCount::default(); groups.len() + 1]; len + 1],
+        };
+
+        row.suffix[len][groups.len()] = BigCount::from(1);
+        for position in (0..len).rev() {
+            for (group, needed) in needed.iter().enumerate() {
+                if len - position < *needed {
+                    continue;
+                }
+                let mut ways = BigCount::default();
+                if row.states[position] != b'#' {
+                    ways = ways + &row.suffix[position + 1][group];
+                }
+                if let Some(next) = row.place(position, group) {
+                    ways = ways + &row.suffix[next][group + 1];
+                }
+                row.suffix[position][group] = ways;
+            }
+        }
+
+        Ok(row)
+    }
+
+    /// Position after the operational spring that ends `group`, if the group
+    /// can start at `position`
+    fn place(&self, position: usize, group: usize) -> Option<usize> {
+        let end = position + self.groups.get(group)?;
+        (end < self.states.len()
+            && self.next_operational[position] >= end
+            && self.states[end] != b'#')
+            .then_some(end + 1)
+    }
+
+    /// Number of arrangements that agree with the row
+    pub fn count(&self) -> BigCount {
+        self.suffix[0][0].clone()
+    }
+
+    /// Ways to place the groups from `group` onwards, starting at `position`
+    ///
+    /// Positions that an arrangement passes through never count more than the
+    /// whole row, so this only fails elsewhere or on rows too large to rank.
+    fn ways(&self, position: usize, group: usize) -> Result<u128, String> {
+        u128::try_from(&self.suffix[position][group])
+    }
+
+    /// Every arrangement, as a string of `#` and `.`, in lexicographic order of
+    /// where the groups start
+    ///
+    /// Branches that can not be completed are never visited, so each
+    /// arrangement is produced in time linear on the row.
+    pub fn arrangements(&self) -> impl Iterator<Item = String> + '_ {
+        let completes =
+            |position: usize, group: usize| self.suffix[position][group] != BigCount::default();
+        let mut stack = vec![(0, 0, String::new())];
+        std::iter::from_fn(move || {
+            while let Some((position, group, arrangement)) = stack.pop() {
+                if position == self.states.len() {
+                    // Drop the padding
+                    return Some(arrangement[..(position - 1)].to_owned());
+                }
+                if self.states[position] != b'#' && completes(position + 1, group) {
+                    stack.push((position + 1, group, arrangement.clone() + "."));
+                }
+                if let Some(next) = self
+                    .place(position, group)
+                    .filter(|next| completes(*next, group + 1))
+                {
+                    let placed = "#".repeat(self.groups[group]) + ".";
+                    stack.push((next, group + 1, arrangement + &placed));
+                }
+            }
+            None
+        })
+    }
+
+    /// Arrangement at `rank` on the order of [`SpringRow::arrangements`]
+    ///
+    /// Fails if the row has more arrangements than a `u128` can rank.
+    pub fn nth_arrangement(&self, mut rank: u128) -> Result<Option<String>, String> {
+        if rank >= self.ways(0, 0)? {
+            return Ok(None);
+        }
+        let mut arrangement = String::new();
+        let (mut position, mut group) = (0, 0);
+        while position + 1 < self.states.len() {
+            let placed = match self.place(position, group) {
+                Some(next) => self.ways(next, group + 1)?,
+                None => 0,
+            };
+            if rank < placed {
+                arrangement += &"#".repeat(self.groups[group]);
+                arrangement.push('.');
+                position += self.groups[group] + 1;
+                group += 1;
+            } else {
+                rank -= placed;
+                arrangement.push('.');
+                position += 1;
+            }
+        }
+        arrangement.truncate(self.states.len() - 1);
+        Ok(Some(arrangement))
+    }
+
+    /// Arrangement picked uniformly at random, using `seed` for the generator
+    pub fn sample(&self, seed: u64) -> Result<Option<String>, String> {
+        let count = self.ways(0, 0)?;
+        if count == 0 {
+            return Ok(None);
+        }
+        // Reject draws from the incomplete block at the top to avoid modulo bias
+        let zone = u128::MAX - u128::MAX % count;
+        let mut state = seed;
+        loop {
+            let draw =
+                (u128::from(splitmix64(&mut state)) << 64) | u128::from(splitmix64(&mut state));
+            if draw < zone {
+                return self.nth_arrangement(draw % count);
+            }
+        }
+    }
+
+    /// What every arrangement agrees on for each spring
+    ///
+    /// A forward pass counts the ways to reach each `(position, group)`, and a
+    /// group placed at a position is part of that many times `suffix`
+    /// arrangements, which are added over the springs it covers. Everything
+    /// wraps, as only the totals, which never exceed the count, need be exact.
+    pub fn deductions(&self) -> Result<Vec<Deduction>, String> {
+        let count = self.ways(0, 0)?;
+        if count == 0 {
+            Err("Row has no arrangement.")?;
+        }
+        let len = self.states.len() - 1;
+        let mut prefix = vec![vec![0u128; self.groups.len() + 1]; len + 2];
+        prefix[0][0] = 1;
+        let mut damaged = vec![0u128; len + 1];
+        for position in 0..=len {
+            for group in 0..=self.groups.len() {
+                let reaching = prefix[position][group];
+                if reaching == 0 {
+                    continue;
+                }
+                if self.states[position] != b'#' {
+                    prefix[position + 1][group] =
+                        prefix[position + 1][group].wrapping_add(reaching);
+                }
+                if let Some(next) = self.place(position, group) {
+                    prefix[next][group + 1] = prefix[next][group + 1].wrapping_add(reaching);
+                    let ways = reaching.wrapping_mul(self.ways(next, group + 1)?);
+                    let size = self.groups[group];
+                    damaged[position] = damaged[position].wrapping_add(ways);
+                    damaged[position + size] = damaged[position + size].wrapping_sub(ways);
+                }
+            }
+        }
+        Ok(damaged[..len]
+            .iter()
+            .scan(0u128, |covering, starting| {
+                *covering = covering.wrapping_add(*starting);
+                Some(*covering)
+            })
+            .map(|covering| match covering {
+                0 => Deduction::Operational,
+                _ if covering == count => Deduction::Damaged,
+                _ => Deduction::Undetermined,
+            })
+            .collect())
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
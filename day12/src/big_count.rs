@@ -0,0 +1,100 @@
+use std::{
+    fmt::{Debug, Display},
+    iter::Sum,
+    ops::Add,
+};
+
+/// Unsigned integer of arbitrary size
+///
+/// Counting arrangements only ever adds, so that is all this supports.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct BigCount {
+    /// Little endian, without trailing zeroes
+    limbs: Vec<u64>,
+}
+
+impl From<u64> for BigCount {
+    fn from(value: u64) -> Self {
+        Self {
+            limbs: if value == 0 { vec![] } else { vec![value] },
+        }
+    }
+}
+
+impl TryFrom<&BigCount> for u128 {
+    type Error = String;
+
+    fn try_from(value: &BigCount) -> Result<Self, Self::Error> {
+        match value.limbs.as_slice() {
+            [] => Ok(0),
+            [low] => Ok(u128::from(*low)),
+            [low, high] => Ok((u128::from(*high) << 64) | u128::from(*low)),
+            _ => Err(format!("{value} does not fit in 128 bits.")),
+        }
+    }
+}
+
+impl Add<&BigCount> for BigCount {
+    type Output = BigCount;
+
+    fn add(mut self, rhs: &BigCount) -> Self::Output {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = false;
+        for (index, limb) in self.limbs.iter_mut().enumerate() {
+            let (sum, overflow_rhs) =
+                limb.overflowing_add(rhs.limbs.get(index).copied().unwrap_or(0));
+            let (sum, overflow_carry) = sum.overflowing_add(u64::from(carry));
+            *limb = sum;
+            carry = overflow_rhs || overflow_carry;
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+        self
+    }
+}
+
+impl Sum for BigCount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, value| sum + &value)
+    }
+}
+
+impl Display for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        // Repeatedly divide by the largest power of 10 that fits in a limb
+        let mut limbs = self.limbs.clone();
+        let mut chunks = vec![];
+        while !limbs.is_empty() {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let current = (remainder << 64) | u128::from(*limb);
+                *limb = (current / u128::from(CHUNK)) as u64;
+                remainder = current % u128::from(CHUNK);
+            }
+            chunks.push(remainder as u64);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((most_significant, rest)) => {
+                write!(f, "{most_significant}")?;
+                rest.iter()
+                    .rev()
+                    .try_for_each(|chunk| write!(f, "{chunk:019}"))
+            }
+        }
+    }
+}
+
+impl Debug for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
@@ -1,19 +1,49 @@
-use std::{collections::BTreeMap, io::Read};
+mod arrangements;
+mod big_count;
+mod nonogram;
+
+use std::io::Read;
+
+use arrangements::{Deduction, SpringRow};
+use big_count::BigCount;
+use nonogram::{Nonogram, Solution};
+
+#[derive(Debug, Default)]
+struct Queries {
+    row: Option<usize>,
+    enumerate: Option<usize>,
+    sample: Option<u64>,
+    deduce: bool,
+    unfold: Option<usize>,
+    nonogram: Option<String>,
+    ppm: Option<String>,
+}
 
 fn main() -> Result<(), String> {
+    let queries = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day12_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
             match file.read_to_string(&mut input) {
                 Ok(_) => {
                     let timer = std::time::Instant::now();
-                    let part1 = springs_arrangements(&input, false);
+                    let part1 = springs_arrangements(&input, 1);
                     println!("{:?}: {part1:?}", timer.elapsed());
 
                     let timer = std::time::Instant::now();
-                    let part2 = springs_arrangements(&input, true);
+                    let part2 = springs_arrangements(&input, 5);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if let Some(unfold) = queries.unfold {
+                        let timer = std::time::Instant::now();
+                        let unfolded = springs_arrangements(&input, unfold);
+                        println!("{:?}: {unfolded:?}", timer.elapsed());
+                    }
+
+                    run_queries(&input, &queries)?;
+                    if let Some(path) = &queries.nonogram {
+                        solve_nonogram(path, queries.ppm.as_deref())?;
+                    }
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,116 +53,149 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn springs_arrangements(input: &str, unfold: bool) -> Result<u64, String> {
-    input
-        .lines()
-        .zip([unfold].into_iter().cycle())
-        .map(|(line, folded)| springs_row_arrangements(line, folded))
-        .sum()
+/// Parses `--unfold FACTOR`, to also count the arrangements of rows unfolded
+/// `FACTOR` times, and `--row INDEX`, the line used by the queries
+/// `--enumerate LIMIT`, `--sample SEED` and `--deduce`, and `--nonogram PATH`,
+/// solving the nonogram in `PATH`, optionally drawn into `--ppm PATH`
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Queries, String> {
+    let mut queries = Queries::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--row" => {
+                queries.row = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse row index. '{err}'"))?,
+                );
+            }
+            "--enumerate" => {
+                queries.enumerate = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse limit. '{err}'"))?,
+                );
+            }
+            "--sample" => {
+                queries.sample = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse seed. '{err}'"))?,
+                );
+            }
+            "--deduce" => queries.deduce = true,
+            "--nonogram" => queries.nonogram = Some(value()?),
+            "--ppm" => queries.ppm = Some(value()?),
+            "--unfold" => {
+                let factor = value()?
+                    .parse()
+                    .map_err(|err| format!("Failed to parse unfold factor. '{err}'"))?;
+                if factor == 0 {
+                    Err("Unfold factor must be at least 1.")?;
+                }
+                queries.unfold = Some(factor);
+            }
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
+    }
+    Ok(queries)
 }
 
-fn springs_row_arrangements(line: &str, unfold: bool) -> Result<u64, String> {
-    if let Some((springs_states, redundance)) = line.split_once(' ') {
-        let redundance = redundance
-            .split(',')
-            .map(str::parse::<usize>)
-            .collect::<Result<Vec<usize>, _>>()
-            .map_err(|err| format!("Failed to read redundant data from line. '{err}'"))?;
-
-        if unfold {
-            fit_arrangement(
-                &vec![springs_states; 5]
-                    .into_iter()
-                    .collect::<Vec<_>>()
-                    .join("?"),
-                vec![redundance; 5]
-                    .into_iter()
-                    .flatten()
-                    .collect::<Vec<_>>()
-                    .as_slice(),
-                &mut BTreeMap::new(),
-            )
-        } else {
-            fit_arrangement(springs_states, &redundance, &mut BTreeMap::new())
+fn run_queries(input: &str, queries: &Queries) -> Result<(), String> {
+    let Some(index) = queries.row else {
+        if queries.enumerate.is_some() || queries.sample.is_some() || queries.deduce {
+            Err("Queries need a '--row'.")?;
+        }
+        return Ok(());
+    };
+    let line = input
+        .lines()
+        .nth(index)
+        .ok_or(format!("Row {index} does not exist."))?;
+    let (springs_states, redundance) = parse_row(line)?;
+    let row = SpringRow::new(springs_states, &redundance)?;
+    println!("{line}: {} arrangements", row.count());
+    if let Some(limit) = queries.enumerate {
+        for arrangement in row.arrangements().take(limit) {
+            println!("{arrangement}");
+        }
+    }
+    if let Some(seed) = queries.sample {
+        match row.sample(seed)? {
+            Some(arrangement) => println!("Sample: {arrangement}"),
+            None => println!("Sample: none"),
         }
-    } else {
-        Err("Failed to split line.".to_owned())
     }
+    if queries.deduce {
+        let deductions = row
+            .deductions()?
+            .into_iter()
+            .map(|deduction| match deduction {
+                Deduction::Damaged => '#',
+                Deduction::Operational => '.',
+                Deduction::Undetermined => '?',
+            })
+            .collect::<String>();
+        println!("Deduced: {deductions}");
+    }
+    Ok(())
 }
 
-fn fit_arrangement<'a>(
-    springs_states: &'a str,
-    redundance: &'a [usize],
-    cache: &mut BTreeMap<(&'a str, &'a [usize]), u64>,
-) -> Result<u64, String> {
-    if let Some(cached) = cache.get(&(springs_states, redundance)) {
-        Ok(*cached)
-    } else {
-        let arrangements = match redundance {
-            [head, tail @ ..] => {
-                if springs_states.len() < *head {
-                    Ok(0)
-                } else {
-                    let mut states = springs_states.chars();
-                    if (&mut states).take(*head).all(|s| matches!(s, '?' | '#')) {
-                        match (springs_states.starts_with('#'), states.next()) {
-                            (true, Some('#')) => Ok(0),
-                            (false, Some('#')) => {
-                                fit_arrangement(&springs_states[1..], redundance, cache)
-                            }
-                            (true, Some('.' | '?')) => {
-                                fit_arrangement(&springs_states[(head + 1)..], tail, cache)
-                            }
-                            (false, Some('.')) => [
-                                if springs_states.chars().take(*head).any(|s| matches!(s, '#')) {
-                                    Ok(0)
-                                } else {
-                                    fit_arrangement(
-                                        &springs_states[(head + 1)..],
-                                        redundance,
-                                        cache,
-                                    )
-                                },
-                                fit_arrangement(&springs_states[(head + 1)..], tail, cache),
-                            ]
-                            .into_iter()
-                            .sum(),
-                            (false, Some('?')) => [
-                                fit_arrangement(&springs_states[(head + 1)..], tail, cache),
-                                fit_arrangement(&springs_states[1..], redundance, cache),
-                            ]
-                            .into_iter()
-                            .sum(),
-
-                            (_, Some(s)) => Err(format!("Invalid state '{s}'.")),
-                            (_, None) => {
-                                if tail.is_empty() {
-                                    Ok(1)
-                                } else {
-                                    Ok(0)
-                                }
-                            }
-                        }
-                    } else if springs_states.starts_with('#') {
-                        Ok(0)
-                    } else {
-                        fit_arrangement(&springs_states[1..], redundance, cache)
-                    }
-                }
-            }
-            [] => {
-                if springs_states.chars().all(|s| matches!(s, '.' | '?')) {
-                    Ok(1)
-                } else {
-                    Ok(0)
-                }
-            }
-        };
-        if let Ok(arrang) = arrangements {
-            cache.insert((springs_states, redundance), arrang);
+fn solve_nonogram(path: &str, ppm: Option<&str>) -> Result<(), String> {
+    let puzzle =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read '{path}'. '{err}'"))?;
+    let solution = match Nonogram::new(&puzzle)?.solve()? {
+        Solution::None => {
+            println!("Nonogram has no solution.");
+            return Ok(());
+        }
+        Solution::Unique(solution) => {
+            println!("{}", nonogram::to_text(&solution));
+            solution
         }
-        arrangements
+        Solution::Multiple(first, second) => {
+            println!("Nonogram has multiple solutions, two of them are");
+            println!("{}\n", nonogram::to_text(&first));
+            println!("{}", nonogram::to_text(&second));
+            first
+        }
+    };
+    if let Some(path) = ppm {
+        let file = std::fs::File::create(path)
+            .map_err(|err| format!("Failed to create '{path}'. '{err}'"))?;
+        let mut writer = std::io::BufWriter::new(file);
+        nonogram::write_ppm(&solution, 8, &mut writer)?;
     }
+    Ok(())
+}
+
+fn parse_row(line: &str) -> Result<(&str, Vec<usize>), String> {
+    let (springs_states, redundance) = line
+        .split_once(' ')
+        .ok_or("Failed to split line.".to_owned())?;
+    let redundance = redundance
+        .split(',')
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|err| format!("Failed to read redundant data from line. '{err}'"))?;
+    Ok((springs_states, redundance))
+}
+
+fn springs_arrangements(input: &str, unfold: usize) -> Result<BigCount, String> {
+    input
+        .lines()
+        .map(|line| springs_row_arrangements(line, unfold))
+        .sum()
+}
+
+/// Arrangements of a row after it is unfolded into `unfold` copies of itself
+fn springs_row_arrangements(line: &str, unfold: usize) -> Result<BigCount, String> {
+    let (springs_states, redundance) = parse_row(line)?;
+    Ok(SpringRow::new(
+        &vec![springs_states; unfold].join("?"),
+        &redundance.repeat(unfold),
+    )?
+    .count())
 }
 
 #[cfg(test)]
@@ -150,21 +213,171 @@ mod tests {
     fn part1_test() {
         let lines_res = [1, 4, 1, 1, 4, 10];
         for (line, res) in PART1_INPUT.lines().zip(lines_res) {
-            assert_eq!(springs_row_arrangements(line, false), Ok(res));
+            assert_eq!(springs_row_arrangements(line, 1), Ok(BigCount::from(res)));
         }
-        assert_eq!(springs_row_arrangements(".??#????.? 2,1", false), Ok(7));
-        assert_eq!(springs_row_arrangements(".?#??..?#??.?? 4,1", false), Ok(1));
+        assert_eq!(
+            springs_row_arrangements(".??#????.? 2,1", 1),
+            Ok(BigCount::from(7))
+        );
+        assert_eq!(
+            springs_row_arrangements(".?#??..?#??.?? 4,1", 1),
+            Ok(BigCount::from(1))
+        );
 
-        assert_eq!(springs_arrangements(PART1_INPUT, false), Ok(21));
+        assert_eq!(springs_arrangements(PART1_INPUT, 1), Ok(BigCount::from(21)));
     }
 
     #[test]
     fn part2_test() {
         let lines_res = [1, 16384, 1, 16, 2500, 506_250];
         for (line, res) in PART1_INPUT.lines().zip(lines_res) {
-            assert_eq!(springs_row_arrangements(line, true), Ok(res));
+            assert_eq!(springs_row_arrangements(line, 5), Ok(BigCount::from(res)));
+        }
+
+        assert_eq!(
+            springs_arrangements(PART1_INPUT, 5),
+            Ok(BigCount::from(525_152))
+        );
+    }
+
+    #[test]
+    fn unfold_test() -> Result<(), String> {
+        // Every unfolded copy of `???.###` has a single arrangement
+        assert_eq!(
+            springs_row_arrangements("???.### 1,1,3", 60),
+            Ok(BigCount::from(1))
+        );
+        // `.??..??...?##.` gains a factor of 8 with every copy after the first
+        let count = springs_row_arrangements(".??..??...?##. 1,1,3", 60)?;
+        assert_eq!(
+            count,
+            (0..177).fold(BigCount::from(4), |sum, _| sum.clone() + &sum)
+        );
+        assert!(u128::try_from(&count).is_err());
+        assert_eq!(
+            BigCount::from(u64::MAX) + &BigCount::from(1),
+            (0..64).fold(BigCount::from(1), |sum, _| sum.clone() + &sum)
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                (0..64).fold(BigCount::from(1), |sum, _| sum.clone() + &sum)
+            ),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                (0..70).fold(BigCount::from(1), |sum, _| sum.clone() + &sum)
+            ),
+            "1180591620717411303424"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn nonogram_test() -> Result<(), String> {
+        // A heart
+        const HEART: &str = r"2,2
+5
+5
+3
+1
+
+3
+4
+4
+4
+3";
+        let expected = ["##.##", "#####", "#####", ".###.", "..#.."];
+        match Nonogram::new(HEART)?.solve()? {
+            Solution::Unique(solution) => {
+                assert_eq!(nonogram::to_text(&solution), expected.join("\n"));
+                let mut ppm = vec![];
+                nonogram::write_ppm(&solution, 2, &mut ppm)?;
+                assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+                assert_eq!(ppm.len(), 13 + 10 * 10 * 3);
+                // (2, 0) is empty, (0, 0) is filled
+                assert_eq!(ppm[13 + 4 * 3], 255);
+                assert_eq!(ppm[13], 0);
+            }
+            other => Err(format!("Expected a unique solution, got {other:?}"))?,
         }
 
-        assert_eq!(springs_arrangements(PART1_INPUT, true), Ok(525_152));
+        // Both diagonals fit these clues
+        let diagonals = Nonogram::new("1\n1\n\n1\n1")?;
+        assert!(matches!(diagonals.solve()?, Solution::Multiple(_, _)));
+
+        let impossible = Nonogram::new("2\n0\n\n1\n0")?;
+        assert_eq!(impossible.solve()?, Solution::None);
+        Ok(())
+    }
+
+    #[test]
+    fn spring_row_test() -> Result<(), String> {
+        for line in PART1_INPUT
+            .lines()
+            .chain([".??#????.? 2,1", ".?#??..?#??.?? 4,1", "?.# 2"])
+        {
+            let (springs_states, redundance) = parse_row(line)?;
+            let row = SpringRow::new(springs_states, &redundance)?;
+            let expected = u128::try_from(&springs_row_arrangements(line, 1)?)?;
+            assert_eq!(u128::try_from(&row.count()), Ok(expected));
+
+            let arrangements = row.arrangements().collect::<Vec<_>>();
+            assert_eq!(arrangements.len() as u128, expected);
+            for (rank, arrangement) in arrangements.iter().enumerate() {
+                assert_eq!(arrangement.len(), springs_states.len());
+                assert!(arrangement
+                    .chars()
+                    .zip(springs_states.chars())
+                    .all(|(a, s)| s == '?' || a == s));
+                assert_eq!(
+                    row.nth_arrangement(rank as u128)?.as_ref(),
+                    Some(arrangement)
+                );
+            }
+            for seed in 0..10 {
+                let sample = row.sample(seed)?;
+                assert!(sample.is_none() || arrangements.contains(&sample.unwrap_or_default()));
+            }
+
+            if let Ok(deductions) = row.deductions() {
+                for (position, deduction) in deductions.into_iter().enumerate() {
+                    let damaged = arrangements
+                        .iter()
+                        .filter(|arrangement| arrangement.as_bytes()[position] == b'#')
+                        .count();
+                    let brute = match damaged {
+                        0 => Deduction::Operational,
+                        _ if damaged == arrangements.len() => Deduction::Damaged,
+                        _ => Deduction::Undetermined,
+                    };
+                    assert_eq!(deduction, brute);
+                }
+            } else {
+                assert!(arrangements.is_empty());
+            }
+        }
+
+        // Too many arrangements to rank, though still counted
+        let row = SpringRow::new(&vec![".??..??...?##."; 60].join("?"), &[1, 1, 3].repeat(60))?;
+        assert!(u128::try_from(&row.count()).is_err());
+        assert!(row.nth_arrangement(0).is_err());
+
+        let row = SpringRow::new("?###????????", &[3, 2, 1])?;
+        assert_eq!(row.arrangements().take(3).count(), 3);
+        let deductions = row.deductions()?;
+        assert_eq!(
+            &deductions[..5],
+            &[
+                Deduction::Operational,
+                Deduction::Damaged,
+                Deduction::Damaged,
+                Deduction::Damaged,
+                Deduction::Operational,
+            ]
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,187 @@
+use std::io::Write;
+
+use common::ppm;
+
+use crate::{arrangements::SpringRow, big_count::BigCount};
+
+/// Grid of `#`, `.` and, while unsolved, `?`
+pub type Grid = Vec<Vec<u8>>;
+
+/// Outcome of solving a nonogram, with at most two of its solutions
+#[derive(Debug, PartialEq, Eq)]
+pub enum Solution {
+    None,
+    Unique(Grid),
+    Multiple(Grid, Grid),
+}
+
+/// Two dimensional puzzle where every row and column is a day 12 line
+#[derive(Debug)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    /// Reads the row clues, a blank line, and the column clues, one clue per
+    /// line as comma separated group sizes, with `0` for an empty line
+    pub fn new(input: &str) -> Result<Self, String> {
+        let mut lines = input.lines().map(str::trim);
+        let mut clues = || {
+            lines
+                .by_ref()
+                .take_while(|line| !line.is_empty())
+                .map(|line| {
+                    line.split(',')
+                        .map(str::parse::<usize>)
+                        .filter(|group| group != &Ok(0))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| format!("Failed to read clue. '{err}'"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let rows = clues()?;
+        let columns = clues()?;
+        if rows.is_empty() || columns.is_empty() {
+            Err("Nonogram needs both row and column clues.")?;
+        }
+        Ok(Self { rows, columns })
+    }
+
+    pub fn solve(&self) -> Result<Solution, String> {
+        let grid = vec![vec![b'?'; self.columns.len()]; self.rows.len()];
+        let mut solutions = vec![];
+        self.search(grid, &mut solutions)?;
+        let mut solutions = solutions.into_iter();
+        Ok(match (solutions.next(), solutions.next()) {
+            (None, _) => Solution::None,
+            (Some(solution), None) => Solution::Unique(solution),
+            (Some(first), Some(second)) => Solution::Multiple(first, second),
+        })
+    }
+
+    /// Propagates the line deductions, then guesses the first unknown cell,
+    /// stopping once two solutions are found
+    fn search(&self, mut grid: Grid, solutions: &mut Vec<Grid>) -> Result<(), String> {
+        if solutions.len() >= 2 || !self.propagate(&mut grid)? {
+            return Ok(());
+        }
+        let unknown = grid
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| row.iter().position(|cell| *cell == b'?').map(|x| (x, y)));
+        match unknown {
+            None => solutions.push(grid),
+            Some((x, y)) => {
+                for guess in [b'#', b'.'] {
+                    let mut guessed = grid.clone();
+                    guessed[y][x] = guess;
+                    self.search(guessed, solutions)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Solves rows and columns until nothing changes, returns `false` if a
+    /// line can not be completed
+    fn propagate(&self, grid: &mut Grid) -> Result<bool, String> {
+        let mut dirty_rows = vec![true; self.rows.len()];
+        let mut dirty_columns = vec![true; self.columns.len()];
+        while dirty_rows.contains(&true) || dirty_columns.contains(&true) {
+            for y in 0..self.rows.len() {
+                if !std::mem::take(&mut dirty_rows[y]) {
+                    continue;
+                }
+                let Some(solved) = solve_line(&grid[y], &self.rows[y])? else {
+                    return Ok(false);
+                };
+                for (x, cell) in solved.into_iter().enumerate() {
+                    if grid[y][x] != cell {
+                        grid[y][x] = cell;
+                        dirty_columns[x] = true;
+                    }
+                }
+            }
+            for x in 0..self.columns.len() {
+                if !std::mem::take(&mut dirty_columns[x]) {
+                    continue;
+                }
+                let column = grid.iter().map(|row| row[x]).collect::<Vec<_>>();
+                let Some(solved) = solve_line(&column, &self.columns[x])? else {
+                    return Ok(false);
+                };
+                for (y, cell) in solved.into_iter().enumerate() {
+                    if grid[y][x] != cell {
+                        grid[y][x] = cell;
+                        dirty_rows[y] = true;
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Fixes every unknown cell of a line that has a single possible state, or
+/// `None` if the line has no arrangement
+///
+/// Each unknown cell is probed as damaged and as operational, and a probe
+/// without arrangements settles the cell to the other state.
+fn solve_line(line: &[u8], clue: &[usize]) -> Result<Option<Vec<u8>>, String> {
+    let fits = |line: &[u8]| -> Result<bool, String> {
+        let states = String::from_utf8_lossy(line);
+        Ok(SpringRow::new(&states, clue)?.count() != BigCount::default())
+    };
+    if !fits(line)? {
+        return Ok(None);
+    }
+    let mut solved = line.to_vec();
+    for (position, cell) in line.iter().enumerate() {
+        if *cell != b'?' {
+            continue;
+        }
+        let mut probe = line.to_vec();
+        probe[position] = b'#';
+        if !fits(&probe)? {
+            solved[position] = b'.';
+            continue;
+        }
+        probe[position] = b'.';
+        if !fits(&probe)? {
+            solved[position] = b'#';
+        }
+    }
+    Ok(Some(solved))
+}
+
+pub fn to_text(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| String::from_utf8_lossy(row).into_owned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the grid as a binary portable pixmap, `scale` pixels per cell
+pub fn write_ppm(grid: &Grid, scale: usize, writer: &mut impl Write) -> Result<(), String> {
+    if scale == 0 {
+        Err("Scale must be at least 1.")?;
+    }
+    let width = grid.first().map_or(0, Vec::len) * scale;
+    let height = grid.len() * scale;
+    let pixels = grid
+        .iter()
+        .flat_map(|row| std::iter::repeat_n(row, scale))
+        .flat_map(|row| {
+            row.iter()
+                .flat_map(|cell| std::iter::repeat_n(*cell, scale))
+        })
+        .flat_map(|cell| match cell {
+            b'#' => [0, 0, 0],
+            b'.' => [255, 255, 255],
+            _ => [128, 128, 128],
+        })
+        .collect::<Vec<u8>>();
+    ppm::write_p6(writer, width, height, &pixels)
+        .map_err(|err| format!("Failed to write image. '{err}'"))
+}
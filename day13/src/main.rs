@@ -1,19 +1,40 @@
+mod reflection;
+
 use std::io::Read;
 
+use common::blocks::{blocks, Block};
+use reflection::{Axis, Pattern};
+
+#[derive(Debug, Default)]
+struct Options {
+    smudges: Option<usize>,
+    report: bool,
+}
+
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day13_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
             match file.read_to_string(&mut input) {
                 Ok(_) => {
                     let timer = std::time::Instant::now();
-                    let part1 = find_reflections(&input, false);
+                    let part1 = find_reflections(&input, 0);
                     println!("{:?}: {part1:?}", timer.elapsed());
 
                     let timer = std::time::Instant::now();
-                    let part2 = find_reflections(&input, true);
+                    let part2 = find_reflections(&input, 1);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if let Some(smudges) = options.smudges {
+                        let timer = std::time::Instant::now();
+                        let total = find_reflections(&input, smudges);
+                        println!("{:?}: {total:?}", timer.elapsed());
+                        if options.report {
+                            report_reflections(&input, smudges)?;
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,78 +44,69 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn find_reflections(input: &str, search_for_smudge: bool) -> Result<u64, String> {
-    input
-        .split("\n\n")
-        .map(|pattern| find_reflection_in_pattern(pattern, search_for_smudge))
-        .sum()
-}
-
-fn find_reflection_in_pattern(pattern: &str, search_for_smudge: bool) -> Result<u64, String> {
-    if let Some(pattern_value) = find_vertical_reflection(pattern, search_for_smudge)? {
-        Ok(pattern_value * 100)
-    } else if let Some(pattern_value) = find_horizontal_reflection(pattern, search_for_smudge)? {
-        Ok(pattern_value)
-    } else {
-        Err(format!("Pattern did not have reflection.\n{pattern}"))
+/// Parses `--smudges K`, summarising the mirrors that need exactly `K` flips,
+/// and `--report`, listing every mirror that needs at most `K`
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--smudges" => {
+                options.smudges = Some(
+                    value()?
+                        .parse()
+                        .map_err(|err| format!("Failed to parse smudge count. '{err}'"))?,
+                );
+            }
+            "--report" => options.report = true,
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
     }
+    if options.report && options.smudges.is_none() {
+        Err("'--report' needs '--smudges'.")?;
+    }
+    Ok(options)
 }
 
-fn find_vertical_reflection(pattern: &str, search_for_smudge: bool) -> Result<Option<u64>, String> {
-    let lines = pattern.lines().collect::<Vec<_>>();
-
-    let search_with_smudge = |i: &usize| -> bool {
-        {
-            let left = &lines[..*i];
-            let right = &lines[*i..];
-            left.iter()
-                .rev()
-                .zip(right)
-                .map(|(l, r)| l.chars().zip(r.chars()).filter(|(l, r)| l.ne(r)).count())
-                .sum::<usize>()
-                .eq(&1usize)
-        }
-    };
-    let search_clean = |i: &usize| -> bool {
-        {
-            let left = &lines[..*i];
-            let right = &lines[*i..];
-            left.iter().rev().zip(right).all(|(l, r)| l.eq(r))
+fn report_reflections(input: &str, max_smudges: usize) -> Result<(), String> {
+    for pattern in blocks(input) {
+        println!("Pattern {}:", pattern.index() + 1);
+        for reflection in Pattern::new(&pattern)?.reflections(max_smudges) {
+            let axis = match reflection.axis {
+                Axis::Rows => "rows",
+                Axis::Columns => "columns",
+            };
+            println!(
+                "  between {axis} {} and {}, {} differences, flip {:?}",
+                reflection.position,
+                reflection.position + 1,
+                reflection.differences(),
+                reflection.smudges
+            );
         }
-    };
-
-    let find_closure: Box<&dyn Fn(&usize) -> bool> = if search_for_smudge {
-        Box::new(&search_with_smudge)
-    } else {
-        Box::new(&search_clean)
-    };
-
-    if let Some(reflection_pos) = (1..lines.len()).find(*find_closure) {
-        u64::try_from(reflection_pos)
-            .map(Some)
-            .map_err(|err| err.to_string())
-    } else {
-        Ok(None)
     }
+    Ok(())
 }
 
-fn find_horizontal_reflection(
-    pattern: &str,
-    search_for_smudge: bool,
-) -> Result<Option<u64>, String> {
-    let line_len = pattern.lines().next().ok_or("Pattern was empty.")?.len();
-    let transpose = (0..line_len)
-        .map(|skip| {
-            pattern
-                .lines()
-                .flat_map(|line| line.chars())
-                .skip(skip)
-                .step_by(line_len)
-                .collect::<String>()
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-    find_vertical_reflection(&transpose, search_for_smudge)
+fn find_reflections(input: &str, smudges: usize) -> Result<u64, String> {
+    blocks(input)
+        .iter()
+        .map(|pattern| find_reflection_in_pattern(pattern, smudges))
+        .sum()
+}
+
+/// Summary of the first mirror that needs exactly `smudges` flips
+fn find_reflection_in_pattern(pattern: &Block, smudges: usize) -> Result<u64, String> {
+    Pattern::new(pattern)?
+        .reflections(smudges)
+        .into_iter()
+        .find(|reflection| reflection.differences() == smudges)
+        .map(|reflection| reflection.summary())
+        .ok_or(format!(
+            "Pattern {} did not have reflection.\n{}",
+            pattern.index() + 1,
+            pattern.text()
+        ))
 }
 
 #[cfg(test)]
@@ -119,11 +131,69 @@ mod tests {
 
     #[test]
     fn part1_test() {
-        assert_eq!(find_reflections(PART1_INPUT, false), Ok(405));
+        assert_eq!(find_reflections(PART1_INPUT, 0), Ok(405));
     }
 
     #[test]
     fn part2_test() {
-        assert_eq!(find_reflections(PART1_INPUT, true), Ok(400));
+        assert_eq!(find_reflections(PART1_INPUT, 1), Ok(400));
+    }
+
+    #[test]
+    fn malformed_separators_test() {
+        let crlf = PART1_INPUT.replace('\n', "\r\n");
+        assert_eq!(find_reflections(&crlf, 0), Ok(405));
+        let spaced = PART1_INPUT.replace("\n\n", "  \n\n \n\n");
+        assert_eq!(find_reflections(&spaced, 1), Ok(400));
+        let ragged = PART1_INPUT.replacen("#....#..#", "#....#..", 1);
+        assert_eq!(
+            find_reflections(&ragged, 0),
+            Err("Block 2, line 10: Expected 9 columns, found 8.".to_owned())
+        );
+    }
+
+    #[test]
+    fn reflections_test() -> Result<(), String> {
+        let patterns = blocks(PART1_INPUT);
+        let first = Pattern::new(&patterns[0])?;
+        let clean = first.reflections(0);
+        assert_eq!(clean.len(), 1);
+        assert_eq!(clean[0].axis, Axis::Columns);
+        assert_eq!(clean[0].position, 5);
+
+        let smudged = first.reflections(1);
+        assert_eq!(smudged.len(), 2);
+        assert_eq!(smudged[0].axis, Axis::Rows);
+        assert_eq!(smudged[0].position, 3);
+        assert_eq!(smudged[0].smudges, vec![(0, 0)]);
+        let budget = first.reflections(2);
+        assert!(smudged.iter().all(|reflection| budget.contains(reflection)));
+
+        let second = Pattern::new(&patterns[1])?;
+        let smudged = second.reflections(1);
+        assert_eq!(smudged[0].axis, Axis::Rows);
+        assert_eq!(smudged[0].position, 1);
+        assert_eq!(smudged[0].smudges, vec![(4, 0)]);
+
+        // Every column of a blank pattern mirrors its neighbours
+        assert_eq!(
+            Pattern::new(&blocks("...\n...")[0])?.reflections(0).len(),
+            3
+        );
+
+        // Patterns wider than a single word of bits
+        let wide = format!("{}#\n{}#", ".".repeat(99), ".".repeat(98));
+        assert_eq!(
+            Pattern::new(&blocks(&wide)[0]).err(),
+            Some("Block 1, line 2: Expected 100 columns, found 99.".to_owned())
+        );
+        let wide = format!("{0}#{0}\n{0}.{0}", ".".repeat(70));
+        let reflections = Pattern::new(&blocks(&wide)[0])?.reflections(1);
+        assert_eq!(reflections[0].axis, Axis::Rows);
+        assert_eq!(reflections[0].smudges, vec![(70, 0)]);
+        assert!(reflections
+            .iter()
+            .any(|reflection| reflection.axis == Axis::Columns && reflection.position == 71));
+        Ok(())
     }
 }
@@ -0,0 +1,121 @@
+use common::blocks::Block;
+
+/// Which lines a mirror sits between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Between two rows, reflecting the pattern top to bottom
+    Rows,
+    /// Between two columns, reflecting the pattern left to right
+    Columns,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    /// Number of rows, or columns, before the mirror
+    pub position: usize,
+    /// Cells, as `(x, y)`, that differ from their mirror image, taken from the
+    /// side before the mirror. Flipping either the cell or its image fixes it.
+    pub smudges: Vec<(usize, usize)>,
+}
+
+impl Reflection {
+    pub fn differences(&self) -> usize {
+        self.smudges.len()
+    }
+
+    /// Value of the reflection on the puzzle's summary
+    pub fn summary(&self) -> u64 {
+        match self.axis {
+            Axis::Rows => self.position as u64 * 100,
+            Axis::Columns => self.position as u64,
+        }
+    }
+}
+
+/// Pattern of ash and rocks, with each row and column packed into bits
+///
+/// Comparing two lines costs one xor and popcount per 64 cells.
+#[derive(Debug)]
+pub struct Pattern {
+    rows: Vec<Vec<u64>>,
+    columns: Vec<Vec<u64>>,
+}
+
+impl Pattern {
+    pub fn new(block: &Block) -> Result<Self, String> {
+        let width = block.width()?;
+        let height = block.lines().len();
+        let mut rows = vec![vec![0u64; width.div_ceil(64)]; height];
+        let mut columns = vec![vec![0u64; height.div_ceil(64)]; width];
+        for (y, (line_number, line)) in block.numbered_lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        rows[y][x / 64] |= 1 << (x % 64);
+                        columns[x][y / 64] |= 1 << (y % 64);
+                    }
+                    '.' => (),
+                    _ => Err(block.error(line_number, &format!("Invalid tile '{c}'.")))?,
+                }
+            }
+        }
+        Ok(Self { rows, columns })
+    }
+
+    /// Every mirror, on both axes, that needs at most `max_smudges` flips,
+    /// mirrors between rows first
+    pub fn reflections(&self, max_smudges: usize) -> Vec<Reflection> {
+        let between_rows =
+            reflections_of(&self.rows, max_smudges).map(|(position, smudges)| Reflection {
+                axis: Axis::Rows,
+                position,
+                smudges,
+            });
+        let between_columns =
+            reflections_of(&self.columns, max_smudges).map(|(position, smudges)| Reflection {
+                axis: Axis::Columns,
+                position,
+                // Lines are columns, so the cells come out transposed
+                smudges: smudges.into_iter().map(|(y, x)| (x, y)).collect(),
+            });
+        between_rows.chain(between_columns).collect()
+    }
+}
+
+/// Mirrors between `lines` with at most `max_smudges` differences, and the
+/// differing cells as `(position along the line, line)`
+fn reflections_of(
+    lines: &[Vec<u64>],
+    max_smudges: usize,
+) -> impl Iterator<Item = (usize, Vec<(usize, usize)>)> + '_ {
+    (1..lines.len()).filter_map(move |position| {
+        let mut differences = 0;
+        let pairs = (0..position).rev().zip(position..lines.len());
+        for (before, after) in pairs.clone() {
+            differences += lines[before]
+                .iter()
+                .zip(&lines[after])
+                .map(|(lhs, rhs)| (lhs ^ rhs).count_ones() as usize)
+                .sum::<usize>();
+            if differences > max_smudges {
+                return None;
+            }
+        }
+        let smudges = pairs
+            .flat_map(|(before, after)| {
+                lines[before]
+                    .iter()
+                    .zip(&lines[after])
+                    .enumerate()
+                    .flat_map(move |(word, (lhs, rhs))| {
+                        let diff = lhs ^ rhs;
+                        (0..64)
+                            .filter(move |bit| diff & (1 << bit) != 0)
+                            .map(move |bit| (word * 64 + bit, before))
+                    })
+            })
+            .collect::<Vec<_>>();
+        Some((position, smudges))
+    })
+}
@@ -1,6 +1,20 @@
+mod platform;
+
 use std::io::Read;
 
+use common::cycle::find_cycle;
+use platform::{parse_program, Direction, Platform, SPIN_CYCLE};
+
+#[derive(Debug)]
+struct Options {
+    program: Option<Vec<Direction>>,
+    cycles: usize,
+    edge: Direction,
+    render: bool,
+}
+
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day14_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -14,6 +28,15 @@ fn main() -> Result<(), String> {
                     let part2 = calculate_load_after_roll_cycle(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if let Some(program) = &options.program {
+                        let timer = std::time::Instant::now();
+                        let platform = run_tilt_program(&input, program, options.cycles)?;
+                        println!("{:?}: {:?}", timer.elapsed(), platform.load(options.edge));
+                        if options.render {
+                            println!("{}", platform.render());
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,209 +46,65 @@ fn main() -> Result<(), String> {
     }
 }
 
-fn calculate_load_after_roll(input: &str) -> Result<u64, String> {
-    let (round_rocks, cube_rocks) = get_rock_locations(input);
-    let rolled_rocks = roll_rocks_north(&round_rocks, &cube_rocks);
-    let rows = input.lines().count();
-    rolled_rocks
-        .iter()
-        .map(|(_rock_x, rock_y)| u64::try_from(rows - rock_y).map_err(|err| err.to_string()))
-        .sum()
-}
-
-fn calculate_load_after_roll_cycle(input: &str) -> Result<u64, String> {
-    const CYCLES: usize = 1_000_000_000;
-    let rows = input.lines().count();
-    let (round_rocks, cube_rocks) = get_rock_locations(input);
-
-    let mut cache = Vec::new();
-    let mut cycled = round_rocks;
-    for i in 0..CYCLES {
-        if i % 1000 == 999 {
-            println!("{i}");
-        }
-        let new = roll_cycle(&cycled, &cube_rocks);
-        cycled = new.clone();
-        if cache.contains(&new) {
-            break;
-        }
-        cache.push(new);
-    }
-
-    if let Some(cycle_start) = cache
-        .iter()
-        .position(|cached_cycle| cached_cycle.eq(&cycled))
-    {
-        let cycle_length = cache.len() - cycle_start;
-        let cycle_offset = (CYCLES - cycle_start) % cycle_length;
-        let arrangement_at_end = cycle_start + cycle_offset - 1;
-
-        cache[arrangement_at_end]
-            .iter()
-            .map(|(_rock_x, rock_y)| u64::try_from(rows - rock_y).map_err(|err| err.to_string()))
-            .sum()
-    } else {
-        Err("Failed to find cycle.".to_owned())
-    }
-}
-
-fn roll_cycle(
-    round_rocks: &[(usize, usize)],
-    cube_rocks: &[(usize, usize)],
-) -> Vec<(usize, usize)> {
-    let north = roll_rocks_north(round_rocks, cube_rocks);
-    let west = roll_rocks_west(&north, cube_rocks);
-    let south = roll_rocks_south(&west, cube_rocks);
-    let mut east = roll_rocks_east(&south, cube_rocks);
-    east.sort_unstable();
-    east
-}
-
-fn roll_rocks_north(
-    round_rocks: &[(usize, usize)],
-    cube_rocks: &[(usize, usize)],
-) -> Vec<(usize, usize)> {
-    let mut rolled = vec![];
-    for (rock_x, rock_y) in round_rocks {
-        let closest_block = cube_rocks
-            .iter()
-            .rev()
-            // Rocks that are on the same column
-            .filter(|(cube_x, _cube_y)| cube_x == rock_x)
-            // First blockage to the north of current rock
-            .find(|(_cube_x, cube_y)| cube_y < rock_y)
-            .copied()
-            .map_or((*rock_x, 0), |(block_x, block_y)| (block_x, block_y + 1));
-        let rocks_between_rock_and_block = round_rocks
-            .iter()
-            // Rocks that are on the same column
-            .filter(|(round_x, _round_y)| round_x == rock_x)
-            // Rocks that are farther than the blockage
-            .filter(|(_round_x, round_y)| round_y >= &closest_block.1)
-            // Rocks between blockage and current rock
-            .filter(|(_round_x, round_y)| round_y < rock_y)
-            .count();
-        rolled.push((*rock_x, closest_block.1 + rocks_between_rock_and_block));
-    }
-    rolled
-}
-
-fn roll_rocks_south(
-    round_rocks: &[(usize, usize)],
-    cube_rocks: &[(usize, usize)],
-) -> Vec<(usize, usize)> {
-    if let Some(south_edge) = cube_rocks.iter().map(|(_, y)| y).max() {
-        let mut rolled = vec![];
-        for (rock_x, rock_y) in round_rocks {
-            let closest_block = cube_rocks
-                .iter()
-                // Rocks that are on the same column
-                .filter(|(cube_x, _cube_y)| cube_x == rock_x)
-                // First blockage to the south of current rock
-                .find(|(_cube_x, cube_y)| cube_y > rock_y)
-                .copied()
-                .map_or((*rock_x, *south_edge), |(block_x, block_y)| {
-                    (block_x, block_y - 1)
-                });
-            let rocks_between_rock_and_block = round_rocks
-                .iter()
-                // Rocks that are on the same column
-                .filter(|(round_x, _round_y)| round_x == rock_x)
-                // Rocks that are farther than the blockage
-                .filter(|(_round_x, round_y)| round_y <= &closest_block.1)
-                // Rocks between blockage and current rock
-                .filter(|(_round_x, round_y)| round_y > rock_y)
-                .count();
-            rolled.push((*rock_x, closest_block.1 - rocks_between_rock_and_block));
+/// Parses `--program DIRECTIONS`, a sequence of tilts such as `NNWSE`, with
+/// `--cycles K`, the times the program runs, `--edge <N|W|S|E>`, where the
+/// load is measured, and `--render`, printing the final platform
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        program: None,
+        cycles: 1,
+        edge: Direction::North,
+        render: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--program" => options.program = Some(parse_program(&value()?)?),
+            "--cycles" => {
+                options.cycles = value()?
+                    .parse()
+                    .map_err(|err| format!("Failed to parse cycle count. '{err}'"))?;
+            }
+            "--edge" => {
+                let edge = value()?;
+                options.edge = match edge.chars().collect::<Vec<_>>().as_slice() {
+                    [direction] => Direction::try_from(*direction)?,
+                    _ => Err(format!("Invalid edge '{edge}'."))?,
+                };
+            }
+            "--render" => options.render = true,
+            other => return Err(format!("Unknown argument '{other}'.")),
         }
-        rolled
-    } else {
-        vec![]
     }
+    Ok(options)
 }
 
-fn roll_rocks_west(
-    round_rocks: &[(usize, usize)],
-    cube_rocks: &[(usize, usize)],
-) -> Vec<(usize, usize)> {
-    let mut rolled = vec![];
-    for (rock_x, rock_y) in round_rocks {
-        let closest_block = cube_rocks
-            .iter()
-            .rev()
-            // Rocks that are on the same row
-            .filter(|(_cube_x, cube_y)| cube_y == rock_y)
-            // First blockage to the west of current rock
-            .find(|(cube_x, _cube_y)| cube_x < rock_x)
-            .copied()
-            .map_or((0, *rock_y), |(block_x, block_y)| (block_x + 1, block_y));
-        let rocks_between_rock_and_block = round_rocks
-            .iter()
-            // Rocks that are on the same row
-            .filter(|(_round_x, round_y)| round_y == rock_y)
-            // Rocks that are farther than the blockage
-            .filter(|(round_x, _round_y)| round_x >= &closest_block.0)
-            // Rocks between blockage and current rock
-            .filter(|(round_x, _round_y)| round_x < rock_x)
-            .count();
-        rolled.push((closest_block.0 + rocks_between_rock_and_block, *rock_y));
-    }
-    rolled
+fn calculate_load_after_roll(input: &str) -> Result<u64, String> {
+    Ok(run_tilt_program(input, &[Direction::North], 1)?.load(Direction::North))
 }
 
-fn roll_rocks_east(
-    round_rocks: &[(usize, usize)],
-    cube_rocks: &[(usize, usize)],
-) -> Vec<(usize, usize)> {
-    if let Some(east_edge) = cube_rocks.iter().map(|(x, _)| x).max() {
-        let mut rolled = vec![];
-        for (rock_x, rock_y) in round_rocks {
-            let closest_block = cube_rocks
-                .iter()
-                // Rocks that are on the same row
-                .filter(|(_cube_x, cube_y)| cube_y == rock_y)
-                // First blockage to the east of current rock
-                .find(|(cube_x, _cube_y)| cube_x > rock_x)
-                .copied()
-                .map_or((*east_edge, *rock_y), |(block_x, block_y)| {
-                    (block_x - 1, block_y)
-                });
-            let rocks_between_rock_and_block = round_rocks
-                .iter()
-                // Rocks that are on the same row
-                .filter(|(_round_x, round_y)| round_y == rock_y)
-                // Rocks that are farther than the blockage
-                .filter(|(round_x, _round_y)| round_x <= &closest_block.0)
-                // Rocks between blockage and current rock
-                .filter(|(round_x, _round_y)| round_x > rock_x)
-                .count();
-            rolled.push((closest_block.0 - rocks_between_rock_and_block, *rock_y));
-        }
-        rolled
-    } else {
-        vec![]
-    }
+fn calculate_load_after_roll_cycle(input: &str) -> Result<u64, String> {
+    const CYCLES: usize = 1_000_000_000;
+    Ok(run_tilt_program(input, &SPIN_CYCLE, CYCLES)?.load(Direction::North))
 }
 
-type RockLists = (Vec<(usize, usize)>, Vec<(usize, usize)>);
-fn get_rock_locations(input: &str) -> RockLists {
-    input
-        .lines()
-        .enumerate()
-        .fold((vec![], vec![]), |(mut round, mut cube), (y, line)| {
-            line.chars().enumerate().for_each(|(x, c)| match c {
-                '#' => cube.push((x, y)),
-                'O' => round.push((x, y)),
-                _ => (),
-            });
-            (round, cube)
-        })
+/// Platform after running `program` `cycles` times
+///
+/// Platforms eventually repeat, so only the cycles until the first repetition
+/// are run.
+fn run_tilt_program(input: &str, program: &[Direction], cycles: usize) -> Result<Platform, String> {
+    let platform = Platform::new(input)?;
+    let states = std::iter::successors(Some(platform), |platform| {
+        let mut cycled = platform.clone();
+        cycled.run_program(program);
+        Some(cycled)
+    });
+    let (cycle, mut history) = find_cycle(states).ok_or("Failed to find cycle.".to_owned())?;
+    Ok(history.swap_remove(cycle.reduce(cycles)))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
-
     use super::*;
 
     const PART1_INPUT: &str = r"O....#....
@@ -284,33 +163,91 @@ O..#.OO...
 #.OOO#...O";
 
     #[test]
-    fn part1_test() {
-        let (round, cube) = get_rock_locations(PART1_INPUT);
-        let (round_rolled, cube_rolled) = get_rock_locations(PART1_INPUT_ROLLED);
-        let rolled = roll_rocks_north(&round, &cube);
-        assert_eq!(cube, cube_rolled);
-        assert_eq!(
-            BTreeSet::from_iter(round_rolled),
-            BTreeSet::from_iter(rolled)
-        );
+    fn part1_test() -> Result<(), String> {
+        let mut platform = Platform::new(PART1_INPUT)?;
+        platform.tilt(Direction::North);
+        assert_eq!(platform, Platform::new(PART1_INPUT_ROLLED)?);
 
         assert_eq!(calculate_load_after_roll(PART1_INPUT), Ok(136));
+        Ok(())
     }
 
     #[test]
-    fn part2_test() {
-        let (round, cube) = get_rock_locations(PART1_INPUT);
-        let mut rolled = round;
+    fn part2_test() -> Result<(), String> {
+        let mut platform = Platform::new(PART1_INPUT)?;
         for input in [PART1_INPUT_CYCLE1, PART1_INPUT_CYCLE2, PART1_INPUT_CYCLE3] {
-            let (round_rolled, cube_rolled) = get_rock_locations(input);
-            rolled = roll_cycle(&rolled, &cube);
-
-            assert_eq!(cube, cube_rolled);
-            assert_eq!(
-                BTreeSet::from_iter(round_rolled),
-                rolled.iter().copied().collect::<BTreeSet<_>>()
-            );
+            platform.run_program(&SPIN_CYCLE);
+            assert_eq!(platform, Platform::new(input)?);
         }
         assert_eq!(calculate_load_after_roll_cycle(PART1_INPUT), Ok(64));
+        Ok(())
+    }
+
+    #[test]
+    fn tilt_program_test() -> Result<(), String> {
+        let program = parse_program("nwse")?;
+        assert_eq!(program, SPIN_CYCLE);
+        assert!(parse_program("NWX").is_err());
+        for (cycles, expected) in [PART1_INPUT_CYCLE1, PART1_INPUT_CYCLE2, PART1_INPUT_CYCLE3]
+            .into_iter()
+            .enumerate()
+        {
+            let platform = run_tilt_program(PART1_INPUT, &program, cycles + 1)?;
+            assert_eq!(platform.render(), expected);
+        }
+        assert_eq!(run_tilt_program(PART1_INPUT, &[], 5)?.render(), PART1_INPUT);
+
+        // Tilting twice towards the same direction changes nothing
+        let rolled = run_tilt_program(PART1_INPUT, &parse_program("NN")?, 3)?;
+        assert_eq!(rolled.render(), PART1_INPUT_ROLLED);
+        assert_eq!(rolled.load(Direction::North), 136);
+
+        // Loads against opposite edges add up to one more than the length of
+        // the line for every rock
+        let platform = Platform::new(PART1_INPUT)?;
+        assert_eq!(
+            platform.load(Direction::North) + platform.load(Direction::South),
+            18 * 11
+        );
+        assert_eq!(
+            platform.load(Direction::West) + platform.load(Direction::East),
+            18 * 11
+        );
+        let rolled_east = run_tilt_program(PART1_INPUT, &[Direction::East], 1)?;
+        assert_eq!(
+            rolled_east.render(),
+            r"....O#....
+.OOO#....#
+.....##...
+.OO#....OO
+......OO#.
+.O#...O#.#
+....O#..OO
+.........O
+#....###..
+#..OO#...."
+        );
+        assert_eq!(rolled_east.load(Direction::East), 105);
+        assert!(rolled_east.load(Direction::East) > platform.load(Direction::East));
+        Ok(())
+    }
+
+    #[test]
+    fn wide_platform_test() -> Result<(), String> {
+        // Rows and columns that span several words of bits
+        let row = format!("{}#{}O", "O.".repeat(50), ".".repeat(60));
+        let input = vec![row; 130].join("\n");
+        let mut platform = Platform::new(&input)?;
+        platform.tilt(Direction::West);
+        let expected = format!("{}{}#O{}", "O".repeat(50), ".".repeat(50), ".".repeat(60));
+        assert_eq!(platform, Platform::new(&vec![expected; 130].join("\n"))?);
+
+        platform.tilt(Direction::South);
+        assert_eq!(platform.load(Direction::North), 51 * (1..=130).sum::<u64>());
+        platform.tilt(Direction::East);
+        platform.tilt(Direction::North);
+        let expected = format!("{}{}#{}O", ".".repeat(50), "O".repeat(50), ".".repeat(60));
+        assert_eq!(platform, Platform::new(&vec![expected; 130].join("\n"))?);
+        Ok(())
     }
 }
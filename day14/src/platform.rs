@@ -0,0 +1,247 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+/// Program of the puzzle's spin cycle
+pub const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
+
+impl TryFrom<char> for Direction {
+    type Error = String;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            'N' => Ok(Self::North),
+            'W' => Ok(Self::West),
+            'S' => Ok(Self::South),
+            'E' => Ok(Self::East),
+            _ => Err(format!("Invalid direction '{value}'.")),
+        }
+    }
+}
+
+/// Reads a tilt program such as `NWSE`, one direction per letter
+pub fn parse_program(program: &str) -> Result<Vec<Direction>, String> {
+    program.chars().map(Direction::try_from).collect()
+}
+
+/// Dish of rocks with the round rocks packed into one bitset per row
+///
+/// The cube rocks never move, so they are kept as the runs of free cells
+/// between them. Tilting counts the round rocks in each run and packs them
+/// against one of its ends, which costs a popcount per 64 cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Platform {
+    width: usize,
+    height: usize,
+    /// Round rocks, bit `x` of row `y`
+    rows: Vec<Vec<u64>>,
+    /// Cube rocks, laid out like `rows`
+    cubes: Vec<Vec<u64>>,
+    /// Free runs of each row
+    row_segments: Vec<Vec<Range<usize>>>,
+    /// Free runs of each column
+    column_segments: Vec<Vec<Range<usize>>>,
+}
+
+impl Platform {
+    pub fn new(input: &str) -> Result<Self, String> {
+        let lines = input.lines().collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |line| line.len());
+        let height = lines.len();
+        let mut rows = vec![vec![0u64; width.div_ceil(64)]; height];
+        let mut cubes = vec![vec![0u64; width.div_ceil(64)]; height];
+        for (y, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                Err(format!("Line {} had a different length.", y + 1))?;
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'O' => rows[y][x / 64] |= 1 << (x % 64),
+                    '#' => cubes[y][x / 64] |= 1 << (x % 64),
+                    '.' => (),
+                    _ => Err(format!("Invalid tile '{c}'."))?,
+                }
+            }
+        }
+        let is_cube = |x: usize, y: usize| cubes[y][x / 64] & (1 << (x % 64)) != 0;
+        let row_segments = (0..height)
+            .map(|y| free_segments((0..width).map(|x| is_cube(x, y))))
+            .collect();
+        let column_segments = (0..width)
+            .map(|x| free_segments((0..height).map(|y| is_cube(x, y))))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            rows,
+            cubes,
+            row_segments,
+            column_segments,
+        })
+    }
+
+    pub fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::West => tilt_lines(&mut self.rows, &self.row_segments, true),
+            Direction::East => tilt_lines(&mut self.rows, &self.row_segments, false),
+            Direction::North | Direction::South => {
+                let mut columns = transpose(&self.rows, self.width);
+                tilt_lines(
+                    &mut columns,
+                    &self.column_segments,
+                    direction == Direction::North,
+                );
+                self.rows = transpose(&columns, self.height);
+            }
+        }
+    }
+
+    /// Tilts towards each direction of `program`, in order
+    pub fn run_program(&mut self, program: &[Direction]) {
+        for direction in program {
+            self.tilt(*direction);
+        }
+    }
+
+    /// Sum, over every round rock, of its distance to the edge opposite to
+    /// `edge`, counting its own line
+    pub fn load(&self, edge: Direction) -> u64 {
+        let mut load = 0;
+        for (y, row) in self.rows.iter().enumerate() {
+            for (word, bits) in row.iter().enumerate() {
+                let mut bits = *bits;
+                while bits != 0 {
+                    let x = word * 64 + bits.trailing_zeros() as usize;
+                    load += match edge {
+                        Direction::North => self.height - y,
+                        Direction::South => y + 1,
+                        Direction::West => self.width - x,
+                        Direction::East => x + 1,
+                    } as u64;
+                    bits &= bits - 1;
+                }
+            }
+        }
+        load
+    }
+
+    /// Platform in the same format it is read from
+    pub fn render(&self) -> String {
+        let is_set =
+            |lines: &[Vec<u64>], x: usize, y: usize| lines[y][x / 64] & (1 << (x % 64)) != 0;
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        if is_set(&self.rows, x, y) {
+                            'O'
+                        } else if is_set(&self.cubes, x, y) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Runs of cells without a cube rock
+fn free_segments(cubes: impl Iterator<Item = bool>) -> Vec<Range<usize>> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut len = 0;
+    for (position, cube) in cubes.enumerate() {
+        if cube {
+            if start < position {
+                segments.push(start..position);
+            }
+            start = position + 1;
+        }
+        len = position + 1;
+    }
+    if start < len {
+        segments.push(start..len);
+    }
+    segments
+}
+
+/// Packs the rocks of every segment against its start, or its end
+fn tilt_lines(lines: &mut [Vec<u64>], segments: &[Vec<Range<usize>>], toward_start: bool) {
+    for (line, segments) in lines.iter_mut().zip(segments) {
+        for segment in segments {
+            let rocks = count_range(line, segment.clone());
+            set_range(line, segment.clone(), false);
+            if toward_start {
+                set_range(line, segment.start..(segment.start + rocks), true);
+            } else {
+                set_range(line, (segment.end - rocks)..segment.end, true);
+            }
+        }
+    }
+}
+
+/// Mask of the bits of `word` that fall inside `range`
+fn word_mask(word: usize, range: &Range<usize>) -> u64 {
+    let low = range.start.saturating_sub(word * 64).min(64);
+    let high = range.end.saturating_sub(word * 64).min(64);
+    let below = |bits: usize| {
+        if bits == 64 {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        }
+    };
+    below(high) & !below(low)
+}
+
+fn count_range(line: &[u64], range: Range<usize>) -> usize {
+    (range.start / 64..range.end.div_ceil(64))
+        .map(|word| (line[word] & word_mask(word, &range)).count_ones() as usize)
+        .sum()
+}
+
+fn set_range(line: &mut [u64], range: Range<usize>, value: bool) {
+    let words = range.start / 64..range.end.div_ceil(64);
+    for (word, bits) in line
+        .iter_mut()
+        .enumerate()
+        .take(words.end)
+        .skip(words.start)
+    {
+        if value {
+            *bits |= word_mask(word, &range);
+        } else {
+            *bits &= !word_mask(word, &range);
+        }
+    }
+}
+
+/// Turns `lines` of `len` bits into `len` lines, visiting only the set bits
+fn transpose(lines: &[Vec<u64>], len: usize) -> Vec<Vec<u64>> {
+    let mut transposed = vec![vec![0u64; lines.len().div_ceil(64)]; len];
+    for (index, line) in lines.iter().enumerate() {
+        for (word, bits) in line.iter().enumerate() {
+            let mut bits = *bits;
+            while bits != 0 {
+                let position = word * 64 + bits.trailing_zeros() as usize;
+                transposed[position][index / 64] |= 1 << (index % 64);
+                bits &= bits - 1;
+            }
+        }
+    }
+    transposed
+}
@@ -1,11 +1,139 @@
-pub fn hash(input: &str) -> Result<u8, std::num::TryFromIntError> {
-    input
-        .as_bytes()
-        .iter()
-        .try_fold(0, |current_value, current_ascii| {
-            let mut wide = u16::from(current_value) + u16::from(*current_ascii);
-            wide *= 17;
-            wide %= 256;
-            u8::try_from(wide)
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    hash::{BuildHasher, Hasher},
+};
+
+/// Parameters of the HASH algorithm, which for every byte adds it to the
+/// current value, multiplies by `multiplier` and takes the remainder of `modulus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashParameters {
+    multiplier: u64,
+    modulus: u64,
+    initial: u64,
+}
+
+impl Default for HashParameters {
+    /// Parameters used by the puzzle
+    fn default() -> Self {
+        Self {
+            multiplier: 17,
+            modulus: 256,
+            initial: 0,
+        }
+    }
+}
+
+impl HashParameters {
+    pub fn new(multiplier: u64, modulus: u64, initial: u64) -> Result<Self, String> {
+        if modulus == 0 {
+            Err("Modulus must be at least 1.")?;
+        }
+        Ok(Self {
+            multiplier,
+            modulus,
+            initial: initial % modulus,
         })
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    pub fn hash(&self, input: &str) -> u64 {
+        let mut hasher = self.build_hasher();
+        hasher.write(input.as_bytes());
+        hasher.finish()
+    }
+}
+
+/// Hasher for [`HashParameters`]
+///
+/// `str` writes a trailing `0xff` when hashed through [`std::hash::Hash`], so
+/// [`HashParameters::hash`] is the one that matches the puzzle.
+#[derive(Debug, Clone)]
+pub struct HolidayHasher {
+    parameters: HashParameters,
+    value: u64,
+}
+
+impl Hasher for HolidayHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let modulus = u128::from(self.parameters.modulus);
+        let multiplier = u128::from(self.parameters.multiplier) % modulus;
+        self.value = bytes.iter().fold(self.value, |value, byte| {
+            // Both factors are below the modulus, so their product fits in 128
+            // bits and the result back into 64
+            let value = (u128::from(value) + u128::from(*byte)) % modulus;
+            (value * multiplier % modulus) as u64
+        });
+    }
+
+    fn finish(&self) -> u64 {
+        self.value
+    }
+}
+
+impl BuildHasher for HashParameters {
+    type Hasher = HolidayHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        HolidayHasher {
+            parameters: *self,
+            value: self.initial,
+        }
+    }
+}
+
+pub fn hash(input: &str) -> Result<u8, std::num::TryFromIntError> {
+    u8::try_from(HashParameters::default().hash(input))
+}
+
+/// How a set of labels spreads over the buckets of a hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketReport<'a> {
+    pub labels: usize,
+    pub used_buckets: usize,
+    /// Most labels that share a bucket
+    pub max_load: usize,
+    /// Buckets holding more than one label, and their labels
+    pub collisions: BTreeMap<u64, Vec<&'a str>>,
+    /// Chi-squared statistic of the bucket loads against a uniform spread,
+    /// close to the number of buckets for a good hash
+    pub chi_squared: f64,
+}
+
+/// Hashes every distinct label and reports how they spread over the buckets
+pub fn analyse<'a>(
+    parameters: &HashParameters,
+    labels: impl IntoIterator<Item = &'a str>,
+) -> BucketReport<'a> {
+    let labels = labels.into_iter().collect::<BTreeSet<_>>();
+    let mut buckets = BTreeMap::<u64, Vec<&str>>::new();
+    for label in &labels {
+        buckets
+            .entry(parameters.hash(label))
+            .or_default()
+            .push(label);
+    }
+    let expected = labels.len() as f64 / parameters.modulus() as f64;
+    let empty_buckets = parameters.modulus() - buckets.len() as u64;
+    let chi_squared = if labels.is_empty() {
+        0.
+    } else {
+        buckets
+            .values()
+            .map(|bucket| (bucket.len() as f64 - expected).powi(2) / expected)
+            .sum::<f64>()
+            + empty_buckets as f64 * expected
+    };
+    BucketReport {
+        labels: labels.len(),
+        used_buckets: buckets.len(),
+        max_load: buckets.values().map(Vec::len).max().unwrap_or(0),
+        chi_squared,
+        collisions: buckets
+            .into_iter()
+            .filter(|(_, bucket)| bucket.len() > 1)
+            .collect(),
+    }
 }
@@ -0,0 +1,137 @@
+use std::fmt::Display;
+
+use crate::hash::hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lens<'a> {
+    pub label: &'a str,
+    pub focal_length: u8,
+}
+
+/// Step of the initialisation sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<'a> {
+    /// `label-`
+    Remove(&'a str),
+    /// `label=focal_length`
+    Insert(Lens<'a>),
+}
+
+impl<'a> Step<'a> {
+    pub fn label(&self) -> &'a str {
+        match self {
+            Self::Remove(label) => label,
+            Self::Insert(lens) => lens.label,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Step<'a> {
+    type Error = String;
+
+    fn try_from(inst: &'a str) -> Result<Self, Self::Error> {
+        if let Some(label) = inst.strip_suffix('-') {
+            Ok(Self::Remove(label))
+        } else if let Some((label, focal_length)) = inst.split_once('=') {
+            let focal_length = focal_length
+                .parse()
+                .map_err(|err| format!("Could not parse focal length. '{err}'"))?;
+            Ok(Self::Insert(Lens {
+                label,
+                focal_length,
+            }))
+        } else {
+            Err(format!("Malformed instruction.'{inst}'"))
+        }
+    }
+}
+
+impl Display for Step<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Remove(label) => write!(f, "{label}-"),
+            Self::Insert(lens) => write!(f, "{}={}", lens.label, lens.focal_length),
+        }
+    }
+}
+
+/// What a step did to the box of its label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    Added(Lens<'a>),
+    /// Old lens and the lens that took its slot
+    Replaced(Lens<'a>, Lens<'a>),
+    Removed(Lens<'a>),
+    /// Removal of a label that was not in the box
+    Unchanged,
+}
+
+/// Boxes of lenses that the initialisation sequence rearranges
+#[derive(Debug, Clone)]
+pub struct LensLibrary<'a> {
+    boxes: Vec<Vec<Lens<'a>>>,
+}
+
+impl Default for LensLibrary<'_> {
+    fn default() -> Self {
+        Self {
+            boxes: vec![vec![]; 256],
+        }
+    }
+}
+
+impl<'a> LensLibrary<'a> {
+    /// Applies `step`, returning the box it touched and what changed there
+    pub fn apply(&mut self, step: Step<'a>) -> Result<(usize, Change<'a>), String> {
+        let box_id = usize::from(hash(step.label()).map_err(|err| err.to_string())?);
+        let lens_box = &mut self.boxes[box_id];
+        let position = lens_box.iter().position(|lens| lens.label == step.label());
+        let change = match (step, position) {
+            (Step::Remove(_), Some(position)) => Change::Removed(lens_box.remove(position)),
+            (Step::Remove(_), None) => Change::Unchanged,
+            (Step::Insert(lens), Some(position)) => {
+                Change::Replaced(std::mem::replace(&mut lens_box[position], lens), lens)
+            }
+            (Step::Insert(lens), None) => {
+                lens_box.push(lens);
+                Change::Added(lens)
+            }
+        };
+        Ok((box_id, change))
+    }
+
+    /// Focusing power of the lenses in one box
+    pub fn box_focusing_power(&self, box_id: usize) -> u64 {
+        self.boxes.get(box_id).map_or(0, |lens_box| {
+            lens_box
+                .iter()
+                .zip(1u64..)
+                .map(|(lens, slot)| (box_id as u64 + 1) * slot * u64::from(lens.focal_length))
+                .sum()
+        })
+    }
+
+    pub fn focusing_power(&self) -> u64 {
+        (0..self.boxes.len())
+            .map(|box_id| self.box_focusing_power(box_id))
+            .sum()
+    }
+
+    /// Boxes that hold lenses, one per line, like `Box 0: [rn 1] [cm 2]`
+    pub fn dump(&self) -> String {
+        self.boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, lens_box)| !lens_box.is_empty())
+            .map(|(box_id, lens_box)| {
+                let lenses = lens_box
+                    .iter()
+                    .map(|lens| format!("[{} {}]", lens.label, lens.focal_length))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("Box {box_id}: {lenses}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
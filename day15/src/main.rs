@@ -1,19 +1,21 @@
 mod hash;
+mod lens_library;
 
 use std::io::Read;
 
-use hash::hash;
+use hash::{analyse, hash, HashParameters};
+use lens_library::{Change, LensLibrary, Step};
 
-#[derive(Debug, Clone)]
-struct LensBox<'a>(pub Vec<Lens<'a>>);
-
-#[derive(Debug, Clone)]
-struct Lens<'a> {
-    pub label: &'a str,
-    pub focal_length: u8,
+#[derive(Debug, Default)]
+struct Options {
+    trace: bool,
+    log: bool,
+    box_powers: bool,
+    analyse: Vec<HashParameters>,
 }
 
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day15_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -27,6 +29,25 @@ fn main() -> Result<(), String> {
                     let part2 = lenses_focusing_power(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if options.trace {
+                        println!("{}", trace_initialization(&input)?);
+                    }
+                    if options.log {
+                        println!("{}", log_initialization(&input)?);
+                    }
+                    for parameters in &options.analyse {
+                        print_bucket_report(&input, parameters)?;
+                    }
+                    if options.box_powers {
+                        let library = run_initialization(&input)?;
+                        for box_id in 0..256 {
+                            let power = library.box_focusing_power(box_id);
+                            if power > 0 {
+                                println!("Box {box_id}: {power}");
+                            }
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -48,69 +69,120 @@ fn initialization_sequence_sum(input: &str) -> Result<u64, String> {
         .sum()
 }
 
-fn lenses_focusing_power(input: &str) -> Result<u64, String> {
-    let instructions = input
-        .lines()
-        .flat_map(|line| line.split(','))
-        .collect::<Vec<_>>();
-
-    let mut boxes = vec![LensBox(vec![]); 256];
-
-    for inst in instructions {
-        if inst.ends_with('-') {
-            let hash =
-                hash::hash(inst.trim_end_matches('-')).map_err(|err| err.to_string())? as usize;
-            let relevant_box = &mut boxes[hash].0;
-            if let Some(pos) = relevant_box
-                .iter()
-                .position(|lens_box| lens_box.label.eq(inst.trim_end_matches('-')))
-            {
-                relevant_box.remove(pos);
-            }
-        } else if let Some((label, focal_length)) = inst.split_once('=') {
-            let hash = hash::hash(label).map_err(|err| err.to_string())? as usize;
-            let focal_length = focal_length
-                .parse()
-                .map_err(|err| format!("Could not parse focal length. '{err}'"))?;
-
-            let relevant_box = &mut boxes[hash].0;
-            if let Some(pos) = relevant_box
-                .iter()
-                .position(|lens_box| lens_box.label.eq(label))
-            {
-                relevant_box[pos].focal_length = focal_length;
-            } else {
-                relevant_box.push(Lens {
-                    label,
-                    focal_length,
-                });
+/// Parses `--trace`, printing the boxes after every step, `--log`, printing
+/// what every step changed, `--box-powers`, printing the focusing power of
+/// every box at the end, and `--analyse MULTIPLIER,MODULUS,INITIAL`, which can
+/// be repeated, reporting how the labels spread with a HASH of those parameters
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--analyse" => {
+                let parameters = value()?;
+                let values = parameters
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<u64>, _>>()
+                    .map_err(|err| format!("Failed to parse hash parameters. '{err}'"))?;
+                match values.as_slice() {
+                    [multiplier, modulus, initial] => {
+                        options
+                            .analyse
+                            .push(HashParameters::new(*multiplier, *modulus, *initial)?)
+                    }
+                    _ => Err(format!("Malformatted hash parameters. '{parameters}'"))?,
+                }
             }
-        } else {
-            Err(format!("Malformed instruction.'{inst}'"))?;
+            "--trace" => options.trace = true,
+            "--log" => options.log = true,
+            "--box-powers" => options.box_powers = true,
+            other => return Err(format!("Unknown argument '{other}'.")),
         }
     }
+    Ok(options)
+}
 
-    boxes
-        .iter()
-        .enumerate()
-        .map(|(box_id, lens_box)| {
-            lens_box
-                .0
-                .iter()
-                .enumerate()
-                .map(|(lens_id, lens)| {
-                    [(box_id + 1), (lens_id + 1), lens.focal_length as usize]
-                        .into_iter()
-                        .map(|val| {
-                            u64::try_from(val).map_err(|err| format!("Hash failed with '{err}'."))
-                        })
-                        .product::<Result<u64, String>>()
-                })
-                .sum::<Result<u64, String>>()
+fn read_steps(input: &str) -> Result<Vec<Step<'_>>, String> {
+    input
+        .lines()
+        .flat_map(|line| line.split(','))
+        .map(Step::try_from)
+        .collect()
+}
+
+fn run_initialization(input: &str) -> Result<LensLibrary<'_>, String> {
+    let mut library = LensLibrary::default();
+    for step in read_steps(input)? {
+        library.apply(step)?;
+    }
+    Ok(library)
+}
+
+fn lenses_focusing_power(input: &str) -> Result<u64, String> {
+    Ok(run_initialization(input)?.focusing_power())
+}
+
+fn print_bucket_report(input: &str, parameters: &HashParameters) -> Result<(), String> {
+    let steps = read_steps(input)?;
+    let report = analyse(parameters, steps.iter().map(Step::label));
+    println!("{parameters:?}");
+    println!(
+        "  {} labels in {} of {} buckets, at most {} per bucket, chi-squared {:.2}",
+        report.labels,
+        report.used_buckets,
+        parameters.modulus(),
+        report.max_load,
+        report.chi_squared
+    );
+    for (bucket, labels) in &report.collisions {
+        println!("  {bucket}: {}", labels.join(" "));
+    }
+    Ok(())
+}
+
+/// Boxes after every step, in the format of the puzzle
+fn trace_initialization(input: &str) -> Result<String, String> {
+    let mut library = LensLibrary::default();
+    read_steps(input)?
+        .into_iter()
+        .map(|step| {
+            library.apply(step)?;
+            Ok(format!("After \"{step}\":\n{}", library.dump()))
         })
-        .collect::<Vec<_>>()
+        .collect::<Result<Vec<_>, String>>()
+        .map(|snapshots| snapshots.join("\n\n"))
+}
+
+/// What every step changed, one step per line
+fn log_initialization(input: &str) -> Result<String, String> {
+    let mut library = LensLibrary::default();
+    read_steps(input)?
         .into_iter()
-        .sum()
+        .map(|step| {
+            let (box_id, change) = library.apply(step)?;
+            Ok(match change {
+                Change::Added(lens) => {
+                    format!(
+                        "{step}: box {box_id} added [{} {}]",
+                        lens.label, lens.focal_length
+                    )
+                }
+                Change::Replaced(old, new) => format!(
+                    "{step}: box {box_id} replaced [{} {}] with [{} {}]",
+                    old.label, old.focal_length, new.label, new.focal_length
+                ),
+                Change::Removed(lens) => {
+                    format!(
+                        "{step}: box {box_id} removed [{} {}]",
+                        lens.label, lens.focal_length
+                    )
+                }
+                Change::Unchanged => format!("{step}: box {box_id} unchanged"),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(|lines| lines.join("\n"))
 }
 
 #[cfg(test)]
@@ -133,4 +205,72 @@ mod tests {
     fn part2_test() {
         assert_eq!(lenses_focusing_power(PART1_INPUT), Ok(145));
     }
+
+    #[test]
+    fn hash_parameters_test() -> Result<(), String> {
+        use std::collections::HashMap;
+
+        let puzzle = HashParameters::default();
+        assert_eq!(puzzle, HashParameters::new(17, 256, 0)?);
+        assert_eq!(puzzle.hash("HASH"), 52);
+        assert!(HashParameters::new(17, 0, 0).is_err());
+        // Adding a byte to the value goes past 64 bits, and multiplying past 128
+        let wide = HashParameters::new(u64::MAX, u64::MAX, u64::MAX - 1)?;
+        assert_eq!(wide.hash("zz"), 0);
+        let wide = HashParameters::new(u64::MAX - 1, u64::MAX, u64::MAX - 1)?;
+        assert_eq!(wide.hash("zz"), u64::MAX - 1);
+        assert_eq!(wide.hash("overflowing"), u64::MAX - 63);
+
+        // Usable as the hasher of a map
+        let mut map = HashMap::with_hasher(puzzle);
+        map.insert("rn", 1);
+        map.insert("cm", 2);
+        assert_eq!(map.get("cm"), Some(&2));
+
+        let steps = read_steps(PART1_INPUT)?;
+        let report = analyse(&puzzle, steps.iter().map(Step::label));
+        assert_eq!(report.labels, 6);
+        assert_eq!(report.used_buckets, 3);
+        assert_eq!(report.max_load, 3);
+        assert_eq!(
+            report.collisions.into_iter().collect::<Vec<_>>(),
+            vec![(0, vec!["cm", "rn"]), (3, vec!["ab", "ot", "pc"])]
+        );
+
+        // A single bucket holds everything
+        let report = analyse(&HashParameters::new(17, 1, 0)?, ["a", "b", "a"]);
+        assert_eq!(report.labels, 2);
+        assert_eq!(report.max_load, 2);
+        assert!(report.chi_squared.abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn lens_library_test() -> Result<(), String> {
+        let trace = trace_initialization(PART1_INPUT)?;
+        let snapshots = trace.split("\n\n").collect::<Vec<_>>();
+        assert_eq!(snapshots.len(), 11);
+        assert_eq!(snapshots[0], "After \"rn=1\":\nBox 0: [rn 1]");
+        assert_eq!(
+            snapshots[3],
+            "After \"cm=2\":\nBox 0: [rn 1] [cm 2]\nBox 1: [qp 3]"
+        );
+        assert_eq!(
+            snapshots[10],
+            "After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]"
+        );
+
+        let log = log_initialization(PART1_INPUT)?;
+        let log = log.lines().collect::<Vec<_>>();
+        assert_eq!(log[1], "cm-: box 0 unchanged");
+        assert_eq!(log[4], "qp-: box 1 removed [qp 3]");
+        assert_eq!(log[10], "ot=7: box 3 replaced [ot 9] with [ot 7]");
+
+        let library = run_initialization(PART1_INPUT)?;
+        assert_eq!(library.box_focusing_power(0), 5);
+        assert_eq!(library.box_focusing_power(3), 140);
+        assert_eq!(library.box_focusing_power(300), 0);
+        assert!(read_steps("rn=1,cm").is_err());
+        Ok(())
+    }
 }
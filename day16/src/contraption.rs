@@ -0,0 +1,556 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Write,
+};
+
+use common::ppm;
+
+use crate::optics::Optics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RayDirection {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// Ray leaving the tile at `(x, y)` in a direction
+pub type Ray = (usize, usize, RayDirection);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleInteraction {
+    Deflaction(RayDirection),
+    Split(RayDirection, RayDirection),
+    SplitThree(RayDirection, RayDirection, RayDirection),
+    /// Beam stops, at an absorber or the edge of the contraption
+    Wall,
+    /// Beam carries on from the other tile drawn the same way
+    Portal,
+}
+
+/// How beams fade as they travel
+///
+/// A beam starts with `intensity`, loses `per_tile` for every tile it moves
+/// and `per_split` whenever a splitter divides it, and only energises the tiles
+/// it crosses with at least `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attenuation {
+    pub intensity: u32,
+    pub per_tile: u32,
+    pub per_split: u32,
+    pub threshold: u32,
+}
+
+impl Default for Attenuation {
+    /// Beams that never fade, like the puzzle's
+    fn default() -> Self {
+        Self {
+            intensity: 1,
+            per_tile: 0,
+            per_split: 0,
+            threshold: 1,
+        }
+    }
+}
+
+impl RayDirection {
+    const ALL: [RayDirection; 4] = [Self::North, Self::South, Self::East, Self::West];
+
+    /// Bit of the direction on a tile's set of beam directions
+    fn bit(self) -> u8 {
+        match self {
+            Self::North => 1,
+            Self::South => 2,
+            Self::East => 4,
+            Self::West => 8,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::East => Self::West,
+            Self::West => Self::East,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::North => Self::West,
+            Self::South => Self::East,
+            Self::East => Self::North,
+            Self::West => Self::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.turn_left().reverse()
+    }
+
+    fn arrow(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::South => 'v',
+            Self::East => '>',
+            Self::West => '<',
+        }
+    }
+}
+
+/// Grid of mirrors and splitters that beams travel through
+#[derive(Debug)]
+pub struct Contraption<'a> {
+    tiles: Vec<&'a [u8]>,
+    optics: Optics,
+    /// Tile that each portal leads to
+    portals: HashMap<(usize, usize), (usize, usize)>,
+}
+
+/// Tiles reached by the beams from one entry point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Energization {
+    /// Directions of the beams that crossed each tile, as bits of [`RayDirection`]
+    beams: Vec<Vec<u8>>,
+}
+
+impl<'a> Contraption<'a> {
+    pub fn new(input: &'a str) -> Result<Self, String> {
+        Self::with_optics(input, Optics::default())
+    }
+
+    /// Contraption whose tiles behave as registered in `optics`
+    pub fn with_optics(input: &'a str, optics: Optics) -> Result<Self, String> {
+        let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let width = tiles.first().map_or(0, |line| line.len());
+        if width == 0 {
+            Err("Contraption was empty.")?;
+        }
+        if let Some(y) = tiles.iter().position(|line| line.len() != width) {
+            Err(format!("Line {} had a different length.", y + 1))?;
+        }
+
+        let mut portal_tiles = HashMap::<u8, Vec<(usize, usize)>>::new();
+        for (y, line) in tiles.iter().enumerate() {
+            for (x, tile) in line.iter().enumerate() {
+                if *tile == b'.' {
+                    continue;
+                }
+                if !optics.is_registered(*tile) {
+                    Err(format!(
+                        "Unknown tile '{}' at {x},{y}.",
+                        char::from(*tile).escape_default()
+                    ))?;
+                }
+                if optics.interaction(*tile, RayDirection::North)
+                    == Some(ObstacleInteraction::Portal)
+                {
+                    portal_tiles.entry(*tile).or_default().push((x, y));
+                }
+            }
+        }
+        let mut portals = HashMap::new();
+        for (tile, ends) in portal_tiles {
+            match ends.as_slice() {
+                [a, b] => {
+                    portals.insert(*a, *b);
+                    portals.insert(*b, *a);
+                }
+                _ => Err(format!(
+                    "Portal '{}' must appear exactly twice.",
+                    char::from(tile)
+                ))?,
+            }
+        }
+
+        Ok(Self {
+            tiles,
+            optics,
+            portals,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Entry points on every edge, pointing into the contraption
+    pub fn entries(&self) -> impl Iterator<Item = Ray> {
+        let (line_length, line_count) = (self.width(), self.height());
+        (0..line_length)
+            .map(|x| (x, 0, RayDirection::South))
+            .chain((0..line_length).map(move |x| (x, line_count - 1, RayDirection::North)))
+            .chain((0..line_count).map(|y| (0, y, RayDirection::East)))
+            .chain((0..line_count).map(move |y| (line_length - 1, y, RayDirection::West)))
+    }
+
+    /// Rays that leave the tile of `start_ray`, which may already be an obstacle
+    pub(crate) fn start_rays(&self, start_ray: Ray) -> Result<Vec<Ray>, String> {
+        let (x, y, dir) = start_ray;
+        if x >= self.width() || y >= self.height() {
+            Err(format!(
+                "Ray started outside of the contraption. '{start_ray:?}'"
+            ))?;
+        }
+        if self.tiles[y][x] == b'.' {
+            Ok(vec![start_ray])
+        } else {
+            self.leave((x, y), dir)
+        }
+    }
+
+    /// Tile where `ray` stops, and the rays that leave that tile
+    pub(crate) fn follow(
+        &self,
+        (ray_x, ray_y, ray_dir): Ray,
+    ) -> Result<((usize, usize), Vec<Ray>), String> {
+        let tiles = &self.tiles;
+        let stop = match ray_dir {
+            RayDirection::North => next_obstacle_north((ray_x, ray_y), tiles),
+            RayDirection::South => next_obstacle_south((ray_x, ray_y), tiles),
+            RayDirection::East => next_obstacle_east((ray_x, ray_y), tiles),
+            RayDirection::West => next_obstacle_west((ray_x, ray_y), tiles),
+        };
+        Ok((stop, self.leave(stop, ray_dir)?))
+    }
+
+    /// Rays that leave the tile at `stop` when a beam travelling in `ray_dir`
+    /// meets it
+    fn leave(&self, stop: (usize, usize), ray_dir: RayDirection) -> Result<Vec<Ray>, String> {
+        let next_dir = get_next_direction(
+            stop,
+            ray_dir,
+            self.tiles[stop.1][stop.0],
+            (self.width(), self.height()),
+            &self.optics,
+        )?;
+        Ok(match next_dir {
+            ObstacleInteraction::Deflaction(def) => vec![(stop.0, stop.1, def)],
+            ObstacleInteraction::Split(split_a, split_b) => {
+                vec![(stop.0, stop.1, split_a), (stop.0, stop.1, split_b)]
+            }
+            ObstacleInteraction::SplitThree(split_a, split_b, split_c) => vec![
+                (stop.0, stop.1, split_a),
+                (stop.0, stop.1, split_b),
+                (stop.0, stop.1, split_c),
+            ],
+            ObstacleInteraction::Wall => vec![],
+            ObstacleInteraction::Portal => {
+                let (x, y) = self
+                    .portals
+                    .get(&stop)
+                    .ok_or(format!("Portal without a pair. '{stop:?}'"))?;
+                vec![(*x, *y, ray_dir)]
+            }
+        })
+    }
+
+    /// Follows the beams that enter at `start_ray`
+    ///
+    /// Beams jump from obstacle to obstacle, and a beam that leaves a tile in a
+    /// direction it already left it in is dropped, so loops end.
+    pub fn energize(&self, start_ray: Ray) -> Result<Energization, String> {
+        self.energize_attenuated(start_ray, &Attenuation::default())
+    }
+
+    /// Follows the beams that enter at `start_ray`, fading as `attenuation` says
+    ///
+    /// A beam only leaves a tile in a direction again if it is brighter than
+    /// the last one that did, so loops end, and beams dimmer than the threshold
+    /// are dropped.
+    pub fn energize_attenuated(
+        &self,
+        start_ray: Ray,
+        attenuation: &Attenuation,
+    ) -> Result<Energization, String> {
+        let mut beams = vec![vec![0u8; self.width()]; self.height()];
+        // Brightest ray started from each tile, by direction
+        let mut started = vec![vec![[0u32; 4]; self.width()]; self.height()];
+        if attenuation.intensity < attenuation.threshold {
+            return Ok(Energization { beams });
+        }
+
+        // The beam lights the tile it enters by, even if it is absorbed there
+        let (start_x, start_y, start_dir) = start_ray;
+        let start_rays = self.start_rays(start_ray)?;
+        beams[start_y][start_x] |= start_dir.bit();
+        let intensity = split_intensity(attenuation.intensity, start_rays.len(), attenuation);
+        let mut rays_to_process = VecDeque::new();
+        for ray in start_rays {
+            add_new_ray_to_queue(
+                ray,
+                intensity,
+                attenuation,
+                &mut started,
+                &mut rays_to_process,
+            );
+        }
+
+        while let Some(((ray_x, ray_y, ray_dir), intensity)) = rays_to_process.pop_front() {
+            let (stop, next_rays) = self.follow((ray_x, ray_y, ray_dir))?;
+            let moved = ray_x.abs_diff(stop.0) + ray_y.abs_diff(stop.1);
+            let faded = intensity.saturating_sub(
+                attenuation
+                    .per_tile
+                    .saturating_mul(moved.try_into().unwrap_or(u32::MAX)),
+            );
+            let next_intensity = split_intensity(faded, next_rays.len(), attenuation);
+            for ray in next_rays {
+                add_new_ray_to_queue(
+                    ray,
+                    next_intensity,
+                    attenuation,
+                    &mut started,
+                    &mut rays_to_process,
+                );
+            }
+
+            // Tiles past the first `lit` are too dim to be energised
+            let lit = match (intensity - attenuation.threshold).checked_div(attenuation.per_tile) {
+                Some(steps) => (steps as usize).min(moved) + 1,
+                None => moved + 1,
+            };
+            energize_grid(&mut beams, (ray_x, ray_y), stop, ray_dir, lit)?;
+        }
+
+        Ok(Energization { beams })
+    }
+}
+
+impl Energization {
+    pub fn energized_count(&self) -> u64 {
+        self.beams
+            .iter()
+            .flatten()
+            .filter(|directions| **directions != 0)
+            .count() as u64
+    }
+
+    pub fn is_energized(&self, (x, y): (usize, usize)) -> bool {
+        self.beams[y][x] != 0
+    }
+
+    /// Directions of the beams that crossed the tile at `(x, y)`
+    pub fn directions(&self, (x, y): (usize, usize)) -> Vec<RayDirection> {
+        RayDirection::ALL
+            .into_iter()
+            .filter(|direction| self.beams[y][x] & direction.bit() != 0)
+            .collect()
+    }
+
+    /// Energised tiles as `#`, like the puzzle
+    pub fn render_energized(&self) -> String {
+        self.beams
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|directions| if *directions == 0 { '.' } else { '#' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Beam paths over the contraption, like the puzzle: empty tiles show the
+    /// arrow of the beam that crossed them, or the number of beams if several did
+    pub fn render_beams(&self, contraption: &Contraption) -> String {
+        (0..contraption.height())
+            .map(|y| {
+                (0..contraption.width())
+                    .map(
+                        |x| match (contraption.tiles[y][x], self.directions((x, y)).as_slice()) {
+                            (b'.', []) => '.',
+                            (b'.', [direction]) => direction.arrow(),
+                            (b'.', directions) => {
+                                char::from_digit(directions.len() as u32, 10).unwrap_or('?')
+                            }
+                            (tile, _) => char::from(tile),
+                        },
+                    )
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Writes the contraption as a binary portable pixmap, `scale` pixels per tile,
+    /// with energised tiles in yellow and obstacles in grey
+    pub fn write_ppm(
+        &self,
+        contraption: &Contraption,
+        scale: usize,
+        writer: &mut impl Write,
+    ) -> Result<(), String> {
+        if scale == 0 {
+            Err("Scale must be at least 1.")?;
+        }
+        let (width, height) = (contraption.width() * scale, contraption.height() * scale);
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for py in 0..height {
+            for px in 0..width {
+                let (x, y) = (px / scale, py / scale);
+                let colour = match (contraption.tiles[y][x], self.is_energized((x, y))) {
+                    (b'.', false) => [20, 20, 20],
+                    (b'.', true) => [255, 210, 0],
+                    (_, false) => [120, 120, 120],
+                    (_, true) => [255, 120, 0],
+                };
+                pixels.extend_from_slice(&colour);
+            }
+        }
+        ppm::write_p6(writer, width, height, &pixels)
+            .map_err(|err| format!("Failed to write image. '{err}'"))
+    }
+}
+
+/// Intensity of each of the `rays` beams that leave a tile
+fn split_intensity(intensity: u32, rays: usize, attenuation: &Attenuation) -> u32 {
+    if rays > 1 {
+        intensity.saturating_sub(attenuation.per_split)
+    } else {
+        intensity
+    }
+}
+
+fn add_new_ray_to_queue(
+    new_ray: Ray,
+    intensity: u32,
+    attenuation: &Attenuation,
+    started: &mut [Vec<[u32; 4]>],
+    rays_to_process: &mut VecDeque<(Ray, u32)>,
+) {
+    let (x, y, dir) = new_ray;
+    let brightest = &mut started[y][x][dir.bit().trailing_zeros() as usize];
+    if intensity >= attenuation.threshold && intensity > *brightest {
+        *brightest = intensity;
+        rays_to_process.push_back((new_ray, intensity));
+    }
+}
+
+fn get_next_direction(
+    stop: (usize, usize),
+    ray_dir: RayDirection,
+    obstacle: u8,
+    grid_size: (usize, usize),
+    optics: &Optics,
+) -> Result<ObstacleInteraction, String> {
+    match (obstacle, ray_dir) {
+        (b'.', RayDirection::North) => {
+            if stop.1 == 0 {
+                Ok(ObstacleInteraction::Wall)
+            } else {
+                Err(format!(
+                    "Ray traveling north stopped unexpectedly. '{stop:?}'",
+                ))
+            }
+        }
+        (b'.', RayDirection::South) => {
+            if stop.1 == grid_size.1 - 1 {
+                Ok(ObstacleInteraction::Wall)
+            } else {
+                Err(format!(
+                    "Ray traveling south stopped unexpectedly. '{stop:?}'",
+                ))
+            }
+        }
+        (b'.', RayDirection::East) => {
+            if stop.0 == grid_size.0 - 1 {
+                Ok(ObstacleInteraction::Wall)
+            } else {
+                Err(format!(
+                    "Ray traveling east stopped unexpectedly. '{stop:?}'",
+                ))
+            }
+        }
+        (b'.', RayDirection::West) => {
+            if stop.0 == 0 {
+                Ok(ObstacleInteraction::Wall)
+            } else {
+                Err(format!(
+                    "Ray traveling west stopped unexpectedly. '{stop:?}'",
+                ))
+            }
+        }
+        _ => optics.interaction(obstacle, ray_dir).ok_or(format!(
+            "Unknown mirror orientation. '{:?}'",
+            char::from_u32(u32::from(obstacle))
+        )),
+    }
+}
+
+/// Marks the first `lit` tiles from `begin` towards `end` with the ray's direction
+fn energize_grid(
+    beams: &mut [Vec<u8>],
+    begin: (usize, usize),
+    end: (usize, usize),
+    ray_dir: RayDirection,
+    lit: usize,
+) -> Result<(), String> {
+    if begin.0 != end.0 && begin.1 != end.1 {
+        Err(format!("Can't process diagonal ray. ({begin:?}, {end:?})"))?;
+    }
+    let (mut x, mut y) = begin;
+    for _ in 0..lit {
+        beams[y][x] |= ray_dir.bit();
+        if (x, y) == end {
+            break;
+        }
+        match ray_dir {
+            RayDirection::North => y -= 1,
+            RayDirection::South => y += 1,
+            RayDirection::East => x += 1,
+            RayDirection::West => x -= 1,
+        }
+    }
+
+    Ok(())
+}
+
+fn next_obstacle_north(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
+    lines
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(y, line)| y < &ray_position.1 && line[ray_position.0] != b'.')
+        .map_or((ray_position.0, 0), |(obstacle_ypos, _)| {
+            (ray_position.0, obstacle_ypos)
+        })
+}
+
+fn next_obstacle_south(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
+    let line_count = lines.len();
+    lines
+        .iter()
+        .enumerate()
+        .position(|(y, line)| y > ray_position.1 && line[ray_position.0] != b'.')
+        .map_or((ray_position.0, line_count - 1), |obstacle_ypos| {
+            (ray_position.0, obstacle_ypos)
+        })
+}
+
+fn next_obstacle_east(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
+    let line_len = lines[ray_position.1].len();
+    lines[ray_position.1]
+        .iter()
+        .enumerate()
+        .position(|(x, tile)| x > ray_position.0 && tile != &b'.')
+        .map_or((line_len - 1, ray_position.1), |obstacle_xpos| {
+            (obstacle_xpos, ray_position.1)
+        })
+}
+
+fn next_obstacle_west(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
+    lines[ray_position.1]
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(x, tile)| x < &ray_position.0 && **tile != b'.')
+        .map_or((0, ray_position.1), |(obstacle_xpos, _)| {
+            (obstacle_xpos, ray_position.1)
+        })
+}
@@ -1,6 +1,26 @@
-use std::{collections::VecDeque, io::Read};
+mod contraption;
+mod optics;
+mod segment_graph;
+
+use std::io::Read;
+
+use contraption::{Attenuation, Contraption, RayDirection};
+use optics::Optics;
+use segment_graph::SegmentGraph;
+
+#[derive(Debug, Default)]
+struct Options {
+    entry: Option<(usize, usize, RayDirection)>,
+    render: bool,
+    ppm: Option<String>,
+    best: bool,
+    contraption: Option<String>,
+    extended: bool,
+    attenuation: Option<Attenuation>,
+}
 
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day16_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -14,6 +34,19 @@ fn main() -> Result<(), String> {
                     let part2 = maximize_energized_tiles(&input);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if options.best
+                        || options.attenuation.is_some()
+                        || options.render
+                        || options.ppm.is_some()
+                    {
+                        let input = match &options.contraption {
+                            Some(path) => std::fs::read_to_string(path)
+                                .map_err(|err| format!("Failed to read '{path}'. '{err}'"))?,
+                            None => input,
+                        };
+                        explore(&input, &options)?;
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,249 +56,124 @@ fn main() -> Result<(), String> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum RayDirection {
-    North,
-    South,
-    East,
-    West,
-}
-
-#[derive(Debug, Clone, Copy)]
-enum ObstacleInteraction {
-    Deflaction(RayDirection),
-    Split(RayDirection, RayDirection),
-    Wall,
-}
-
-fn energized_tiles(input: &str) -> Result<u64, String> {
-    let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
-
-    run_energization_for_starting_tile(&tiles, (0, 0, RayDirection::East))
-}
-
-fn maximize_energized_tiles(input: &str) -> Result<u64, String> {
-    let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
-
-    let line_count = tiles.len();
-    let line_length = tiles[0].len();
-
-    (0..line_length)
-        .map(|x| (x, 0, RayDirection::South))
-        .chain((0..line_length).map(|x| (x, line_count - 1, RayDirection::North)))
-        .chain((0..line_count).map(|y| (0, y, RayDirection::East)))
-        .chain((0..line_count).map(|y| (line_length - 1, y, RayDirection::West)))
-        .map(|start_ray| run_energization_for_starting_tile(&tiles, start_ray))
-        .max()
-        .ok_or("Could not find count of energized tiles.")?
-}
-
-fn run_energization_for_starting_tile(
-    tiles: &[&[u8]],
-    start_ray: (usize, usize, RayDirection),
-) -> Result<u64, String> {
-    let mut energized = vec![vec![false; tiles[0].len()]; tiles.len()];
-
-    let line_count = tiles.len();
-    let line_length = tiles[0].len();
-
-    let start_rays = get_next_direction(
-        (start_ray.0, start_ray.1),
-        start_ray.2,
-        tiles[start_ray.1][start_ray.0],
-        (line_length, line_count),
-    )
-    .and_then(|obst_inter| match obst_inter {
-        ObstacleInteraction::Deflaction(dir) => Ok(vec![(start_ray.0, start_ray.1, dir)]),
-        ObstacleInteraction::Split(split_a, split_b) => Ok(vec![
-            (start_ray.0, start_ray.1, split_a),
-            (start_ray.0, start_ray.1, split_b),
-        ]),
-        ObstacleInteraction::Wall => Err("Ray started point to wall.".to_owned()),
-    })
-    .unwrap_or(vec![start_ray]);
-
-    let mut rays_to_process = VecDeque::from_iter(start_rays);
-    let mut rays_cache = vec![];
-
-    while let Some((ray_x, ray_y, ray_dir)) = rays_to_process.pop_front() {
-        rays_cache.push((ray_x, ray_y, ray_dir));
-        let stop = match ray_dir {
-            RayDirection::North => next_obstacle_north((ray_x, ray_y), tiles),
-            RayDirection::South => next_obstacle_south((ray_x, ray_y), tiles),
-            RayDirection::East => next_obstacle_east((ray_x, ray_y), tiles),
-            RayDirection::West => next_obstacle_west((ray_x, ray_y), tiles),
-        };
-        let obstacle = tiles[stop.1][stop.0];
-
-        let next_dir = get_next_direction(stop, ray_dir, obstacle, (line_length, line_count))?;
-
-        match next_dir {
-            ObstacleInteraction::Deflaction(def) => {
-                add_new_ray_to_queue((stop.0, stop.1, def), &rays_cache, &mut rays_to_process);
-            }
-            ObstacleInteraction::Split(split_a, split_b) => {
-                add_new_ray_to_queue((stop.0, stop.1, split_a), &rays_cache, &mut rays_to_process);
-                add_new_ray_to_queue((stop.0, stop.1, split_b), &rays_cache, &mut rays_to_process);
-            }
-            ObstacleInteraction::Wall => (),
-        };
-
-        energize_grid(&mut energized, (ray_x, ray_y), stop)?;
+/// Parses `--entry X,Y,<N|S|E|W>`, the beam followed by `--render`, which
+/// prints its paths and energised tiles, and `--ppm PATH`, which draws them,
+/// `--best`, which reports the entry that energises the most tiles, and how
+/// many tiles the `--entry` energises if one is given, and
+/// `--attenuation INTENSITY,PER_TILE,PER_SPLIT,THRESHOLD`, which fades the
+/// beam of `--entry`
+///
+/// These read the puzzle input, or the contraption at `--contraption PATH`,
+/// which may use the tiles of [`Optics::extended`] with `--extended`.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for '{arg}'."));
+        match arg.as_str() {
+            "--entry" => options.entry = Some(parse_entry(&value()?)?),
+            "--render" => options.render = true,
+            "--ppm" => options.ppm = Some(value()?),
+            "--best" => options.best = true,
+            "--contraption" => options.contraption = Some(value()?),
+            "--extended" => options.extended = true,
+            "--attenuation" => options.attenuation = Some(parse_attenuation(&value()?)?),
+            other => return Err(format!("Unknown argument '{other}'.")),
+        }
     }
-
-    energized
-        .iter()
-        .map(|tile| u64::try_from(tile.iter().filter(|energy| **energy).count()))
-        .sum::<Result<u64, std::num::TryFromIntError>>()
-        .map_err(|err| format!("Failed to calculate energized tiles count. '{err}'"))
+    Ok(options)
 }
 
-fn add_new_ray_to_queue(
-    new_ray: (usize, usize, RayDirection),
-    ray_cache: &[(usize, usize, RayDirection)],
-    rays_to_process: &mut VecDeque<(usize, usize, RayDirection)>,
-) {
-    if !ray_cache.contains(&new_ray) {
-        rays_to_process.push_back(new_ray);
+fn parse_entry(entry: &str) -> Result<(usize, usize, RayDirection), String> {
+    let malformatted = || format!("Malformatted entry. '{entry}'");
+    match entry.split(',').collect::<Vec<_>>().as_slice() {
+        [x, y, direction] => Ok((
+            x.parse().map_err(|_| malformatted())?,
+            y.parse().map_err(|_| malformatted())?,
+            match *direction {
+                "N" => RayDirection::North,
+                "S" => RayDirection::South,
+                "E" => RayDirection::East,
+                "W" => RayDirection::West,
+                _ => Err(malformatted())?,
+            },
+        )),
+        _ => Err(malformatted()),
     }
 }
 
-fn get_next_direction(
-    stop: (usize, usize),
-    ray_dir: RayDirection,
-    obstacle: u8,
-    grid_size: (usize, usize),
-) -> Result<ObstacleInteraction, String> {
-    match (obstacle, ray_dir) {
-        (b'|', RayDirection::East | RayDirection::West) => Ok(ObstacleInteraction::Split(
-            RayDirection::North,
-            RayDirection::South,
-        )),
-        (b'-', RayDirection::North | RayDirection::South) => Ok(ObstacleInteraction::Split(
-            RayDirection::East,
-            RayDirection::West,
-        )),
-        (b'|' | b'-', dir) => Ok(ObstacleInteraction::Deflaction(dir)),
-        (b'/', RayDirection::North) | (b'\\', RayDirection::South) => {
-            Ok(ObstacleInteraction::Deflaction(RayDirection::East))
-        }
-        (b'/', RayDirection::South) | (b'\\', RayDirection::North) => {
-            Ok(ObstacleInteraction::Deflaction(RayDirection::West))
-        }
-        (b'/', RayDirection::East) | (b'\\', RayDirection::West) => {
-            Ok(ObstacleInteraction::Deflaction(RayDirection::North))
-        }
-        (b'/', RayDirection::West) | (b'\\', RayDirection::East) => {
-            Ok(ObstacleInteraction::Deflaction(RayDirection::South))
-        }
-        (b'.', RayDirection::North) => {
-            if stop.1 == 0 {
-                Ok(ObstacleInteraction::Wall)
-            } else {
-                Err(format!(
-                    "Ray traveling north stopped unexpectedly. '{stop:?}'",
-                ))
-            }
-        }
-        (b'.', RayDirection::South) => {
-            if stop.1 == grid_size.1 - 1 {
-                Ok(ObstacleInteraction::Wall)
-            } else {
-                Err(format!(
-                    "Ray traveling south stopped unexpectedly. '{stop:?}'",
-                ))
-            }
-        }
-        (b'.', RayDirection::East) => {
-            if stop.0 == grid_size.0 - 1 {
-                Ok(ObstacleInteraction::Wall)
-            } else {
-                Err(format!(
-                    "Ray traveling east stopped unexpectedly. '{stop:?}'",
-                ))
-            }
-        }
-        (b'.', RayDirection::West) => {
-            if stop.0 == 0 {
-                Ok(ObstacleInteraction::Wall)
-            } else {
-                Err(format!(
-                    "Ray traveling west stopped unexpectedly. '{stop:?}'",
-                ))
-            }
-        }
-        _ => Err(format!(
-            "Unknown mirror orientation. '{:?}'",
-            char::from_u32(u32::from(obstacle))
-        )),
+fn parse_attenuation(attenuation: &str) -> Result<Attenuation, String> {
+    let values = attenuation
+        .split(',')
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|err| format!("Malformatted attenuation. '{err}'"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    match values.as_slice() {
+        [intensity, per_tile, per_split, threshold] => Ok(Attenuation {
+            intensity: *intensity,
+            per_tile: *per_tile,
+            per_split: *per_split,
+            threshold: *threshold,
+        }),
+        _ => Err(format!("Malformatted attenuation. '{attenuation}'")),
     }
 }
 
-fn energize_grid(
-    energized: &mut [Vec<bool>],
-    begin: (usize, usize),
-    end: (usize, usize),
-) -> Result<(), String> {
-    match (begin, end) {
-        ((bx, by), (ex, ey)) if bx == ex => {
-            energized[by.min(ey)..=by.max(ey)]
-                .iter_mut()
-                .for_each(|tile| tile[bx] = true);
-        }
-        ((bx, by), (ex, ey)) if by == ey => {
-            energized[by][bx.min(ex)..=bx.max(ex)]
-                .iter_mut()
-                .for_each(|tile| *tile = true);
+fn explore(input: &str, options: &Options) -> Result<(), String> {
+    let optics = if options.extended {
+        Optics::extended()?
+    } else {
+        Optics::default()
+    };
+    let contraption = Contraption::with_optics(input, optics)?;
+    let entry = options.entry.unwrap_or((0, 0, RayDirection::East));
+
+    if options.best {
+        let timer = std::time::Instant::now();
+        let graph = SegmentGraph::new(&contraption)?;
+        let best = graph.best_entry();
+        println!("{:?}: {best:?}", timer.elapsed());
+        if let Some(entry) = options.entry {
+            let timer = std::time::Instant::now();
+            let count = graph.energized_count(entry);
+            println!("{:?}: {count:?}", timer.elapsed());
         }
-        _ => Err(format!("Can't process diagonal ray. ({begin:?}, {end:?})"))?,
     }
 
+    let attenuation = options.attenuation.unwrap_or_default();
+    let energization = contraption.energize_attenuated(entry, &attenuation)?;
+    if options.attenuation.is_some() {
+        println!("{:?}", energization.energized_count());
+    }
+    if options.render {
+        println!("{}\n", energization.render_beams(&contraption));
+        println!("{}", energization.render_energized());
+    }
+    if let Some(path) = &options.ppm {
+        let file = std::fs::File::create(path)
+            .map_err(|err| format!("Failed to create '{path}'. '{err}'"))?;
+        let mut writer = std::io::BufWriter::new(file);
+        energization.write_ppm(&contraption, 4, &mut writer)?;
+    }
     Ok(())
 }
 
-fn next_obstacle_north(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
-    lines
-        .iter()
-        .enumerate()
-        .rev()
-        .find(|(y, line)| y < &ray_position.1 && line[ray_position.0] != b'.')
-        .map_or((ray_position.0, 0), |(obstacle_ypos, _)| {
-            (ray_position.0, obstacle_ypos)
-        })
-}
-
-fn next_obstacle_south(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
-    let line_count = lines.len();
-    lines
-        .iter()
-        .enumerate()
-        .position(|(y, line)| y > ray_position.1 && line[ray_position.0] != b'.')
-        .map_or((ray_position.0, line_count - 1), |obstacle_ypos| {
-            (ray_position.0, obstacle_ypos)
-        })
+fn energized_tiles(input: &str) -> Result<u64, String> {
+    Ok(Contraption::new(input)?
+        .energize((0, 0, RayDirection::East))?
+        .energized_count())
 }
 
-fn next_obstacle_east(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
-    let line_len = lines[ray_position.1].len();
-    lines[ray_position.1]
-        .iter()
-        .enumerate()
-        .position(|(x, tile)| x > ray_position.0 && tile != &b'.')
-        .map_or((line_len - 1, ray_position.1), |obstacle_xpos| {
-            (obstacle_xpos, ray_position.1)
-        })
+fn maximize_energized_tiles(input: &str) -> Result<u64, String> {
+    Ok(best_entry(input)?.1)
 }
 
-fn next_obstacle_west(ray_position: (usize, usize), lines: &[&[u8]]) -> (usize, usize) {
-    lines[ray_position.1]
-        .iter()
-        .enumerate()
-        .rev()
-        .find(|(x, tile)| x < &ray_position.0 && **tile != b'.')
-        .map_or((0, ray_position.1), |(obstacle_xpos, _)| {
-            (obstacle_xpos, ray_position.1)
-        })
+fn best_entry(input: &str) -> Result<((usize, usize, RayDirection), u64), String> {
+    SegmentGraph::new(&Contraption::new(input)?)?
+        .best_entry()
+        .ok_or("Could not find count of energized tiles.".to_owned())
 }
 
 #[cfg(test)]
@@ -292,4 +200,153 @@ mod tests {
     fn part2_test() {
         assert_eq!(maximize_energized_tiles(PART1_INPUT), Ok(51));
     }
+
+    #[test]
+    fn contraption_test() -> Result<(), String> {
+        let contraption = Contraption::new(PART1_INPUT)?;
+        let energization = contraption.energize((0, 0, RayDirection::East))?;
+        assert_eq!(
+            energization.render_energized(),
+            r"######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#.."
+        );
+        assert_eq!(
+            energization.render_beams(&contraption),
+            r">|<<<\....
+|v-.\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\
+.v../2\\..
+<->-/vv|..
+.|<<<2-|.\
+.v//.|.v.."
+        );
+        assert_eq!(
+            energization.directions((5, 6)),
+            vec![RayDirection::South, RayDirection::East]
+        );
+
+        let mut ppm = vec![];
+        energization.write_ppm(&contraption, 1, &mut ppm)?;
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(ppm.len(), 13 + 10 * 10 * 3);
+
+        assert!(contraption.energize((10, 0, RayDirection::West)).is_err());
+        assert_eq!(parse_entry("3,0,S"), Ok((3, 0, RayDirection::South)));
+        assert!(parse_entry("3,0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn segment_graph_test() -> Result<(), String> {
+        let contraption = Contraption::new(PART1_INPUT)?;
+        let graph = SegmentGraph::new(&contraption)?;
+        for entry in contraption.entries() {
+            assert_eq!(
+                graph.energized_count(entry),
+                Ok(contraption.energize(entry)?.energized_count())
+            );
+        }
+        assert_eq!(
+            best_entry(PART1_INPUT),
+            Ok(((3, 0, RayDirection::South), 51))
+        );
+        assert!(graph.energized_count((3, 3, RayDirection::South)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn optics_test() -> Result<(), String> {
+        let count = |input: &str, entry| -> Result<u64, String> {
+            Ok(Contraption::with_optics(input, Optics::extended()?)?
+                .energize(entry)?
+                .energized_count())
+        };
+        assert_eq!(count(".#..", (0, 0, RayDirection::East)), Ok(2));
+        assert_eq!(count("#...", (0, 0, RayDirection::East)), Ok(1));
+        assert_eq!(count(".A.A.", (0, 0, RayDirection::East)), Ok(4));
+        assert_eq!(count("...\n.+.\n...", (1, 0, RayDirection::South)), Ok(5));
+        assert_eq!(count(".<..", (0, 0, RayDirection::East)), Ok(2));
+        assert_eq!(count(".<..", (3, 0, RayDirection::West)), Ok(4));
+        assert!(Contraption::new(".#.").is_err());
+        assert!(Contraption::with_optics(".A.", Optics::extended()?).is_err());
+
+        let mut optics = Optics::default();
+        optics.register(b'*', |dir| {
+            contraption::ObstacleInteraction::Deflaction(dir.reverse())
+        })?;
+        assert!(optics
+            .register(b'.', |_| contraption::ObstacleInteraction::Wall)
+            .is_err());
+        let contraption = Contraption::with_optics("..*", optics)?;
+        assert_eq!(
+            contraption
+                .energize((0, 0, RayDirection::East))?
+                .directions((1, 0)),
+            vec![RayDirection::East, RayDirection::West]
+        );
+
+        let contraption =
+            Contraption::with_optics(".A.#.\n..+..\n<.../\n.v.A.\n..-.\\", Optics::extended()?)?;
+        let graph = SegmentGraph::new(&contraption)?;
+        for entry in contraption.entries() {
+            assert_eq!(
+                graph.energized_count(entry),
+                Ok(contraption.energize(entry)?.energized_count())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn attenuation_test() -> Result<(), String> {
+        let count = |input: &str, entry, attenuation| -> Result<u64, String> {
+            Ok(Contraption::new(input)?
+                .energize_attenuated(entry, &attenuation)?
+                .energized_count())
+        };
+        let fading = Attenuation {
+            intensity: 5,
+            per_tile: 1,
+            per_split: 0,
+            threshold: 2,
+        };
+        assert_eq!(
+            count("..........", (0, 0, RayDirection::East), fading),
+            Ok(4)
+        );
+        let splitting = Attenuation {
+            intensity: 3,
+            per_tile: 0,
+            per_split: 2,
+            threshold: 2,
+        };
+        let input = "...\n.|.\n...";
+        assert_eq!(count(input, (0, 1, RayDirection::East), splitting), Ok(2));
+        assert_eq!(
+            count(input, (0, 1, RayDirection::East), Attenuation::default()),
+            Ok(4)
+        );
+        let bright = Attenuation {
+            intensity: 100,
+            ..Attenuation::default()
+        };
+        assert_eq!(
+            count(PART1_INPUT, (0, 0, RayDirection::East), bright),
+            Ok(46)
+        );
+        assert_eq!(parse_attenuation("5,1,0,2"), Ok(fading));
+        assert!(parse_attenuation("5,1,0").is_err());
+        Ok(())
+    }
 }
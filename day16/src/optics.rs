@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use crate::contraption::{ObstacleInteraction, RayDirection};
+
+/// What a kind of tile does to a beam, given the direction the beam travels in
+pub type TileOptics = fn(RayDirection) -> ObstacleInteraction;
+
+/// Kinds of tiles a contraption may hold, besides the empty `.`
+#[derive(Debug, Clone)]
+pub struct Optics {
+    tiles: HashMap<u8, TileOptics>,
+}
+
+impl Default for Optics {
+    /// Mirrors and splitters of the puzzle
+    fn default() -> Self {
+        let mut tiles = HashMap::<u8, TileOptics>::new();
+        tiles.insert(b'/', mirror_slash);
+        tiles.insert(b'\\', mirror_backslash);
+        tiles.insert(b'|', splitter_vertical);
+        tiles.insert(b'-', splitter_horizontal);
+        Self { tiles }
+    }
+}
+
+impl Optics {
+    /// Puzzle tiles, plus `#` absorbers, `>`, `<`, `^` and `v` one-way mirrors,
+    /// `+` three-way splitters and `A` to `Z` portals
+    pub fn extended() -> Result<Self, String> {
+        let mut optics = Self::default();
+        let extra: [(u8, TileOptics); 6] = [
+            (b'#', |_| ObstacleInteraction::Wall),
+            (b'>', |dir| one_way_mirror(RayDirection::East, dir)),
+            (b'<', |dir| one_way_mirror(RayDirection::West, dir)),
+            (b'^', |dir| one_way_mirror(RayDirection::North, dir)),
+            (b'v', |dir| one_way_mirror(RayDirection::South, dir)),
+            (b'+', |dir| {
+                ObstacleInteraction::SplitThree(dir, dir.turn_left(), dir.turn_right())
+            }),
+        ];
+        for (tile, tile_optics) in extra {
+            optics.register(tile, tile_optics)?;
+        }
+        for tile in b'A'..=b'Z' {
+            optics.register(tile, |_| ObstacleInteraction::Portal)?;
+        }
+        Ok(optics)
+    }
+
+    /// Adds, or replaces, the kind of tile drawn as `tile`
+    pub fn register(&mut self, tile: u8, optics: TileOptics) -> Result<(), String> {
+        if tile == b'.' || !tile.is_ascii_graphic() {
+            Err(format!(
+                "Tile '{}' can't be registered.",
+                char::from(tile).escape_default()
+            ))?;
+        }
+        self.tiles.insert(tile, optics);
+        Ok(())
+    }
+
+    pub fn is_registered(&self, tile: u8) -> bool {
+        self.tiles.contains_key(&tile)
+    }
+
+    pub fn interaction(&self, tile: u8, ray_dir: RayDirection) -> Option<ObstacleInteraction> {
+        self.tiles.get(&tile).map(|optics| optics(ray_dir))
+    }
+}
+
+fn mirror_slash(ray_dir: RayDirection) -> ObstacleInteraction {
+    ObstacleInteraction::Deflaction(match ray_dir {
+        RayDirection::North => RayDirection::East,
+        RayDirection::South => RayDirection::West,
+        RayDirection::East => RayDirection::North,
+        RayDirection::West => RayDirection::South,
+    })
+}
+
+fn mirror_backslash(ray_dir: RayDirection) -> ObstacleInteraction {
+    ObstacleInteraction::Deflaction(match ray_dir {
+        RayDirection::North => RayDirection::West,
+        RayDirection::South => RayDirection::East,
+        RayDirection::East => RayDirection::South,
+        RayDirection::West => RayDirection::North,
+    })
+}
+
+fn splitter_vertical(ray_dir: RayDirection) -> ObstacleInteraction {
+    match ray_dir {
+        RayDirection::East | RayDirection::West => {
+            ObstacleInteraction::Split(RayDirection::North, RayDirection::South)
+        }
+        dir => ObstacleInteraction::Deflaction(dir),
+    }
+}
+
+fn splitter_horizontal(ray_dir: RayDirection) -> ObstacleInteraction {
+    match ray_dir {
+        RayDirection::North | RayDirection::South => {
+            ObstacleInteraction::Split(RayDirection::East, RayDirection::West)
+        }
+        dir => ObstacleInteraction::Deflaction(dir),
+    }
+}
+
+/// Lets beams through, except the ones travelling against `pass`, which it
+/// sends back where they came from
+fn one_way_mirror(pass: RayDirection, ray_dir: RayDirection) -> ObstacleInteraction {
+    if ray_dir == pass.reverse() {
+        ObstacleInteraction::Deflaction(pass)
+    } else {
+        ObstacleInteraction::Deflaction(ray_dir)
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use crate::contraption::{Contraption, Ray};
+
+/// Beam segments of a contraption, from every tile a ray can leave to the
+/// obstacle that stops it, condensed into their strongly connected components
+///
+/// Beams that loop share every tile of the loop, so each component is
+/// energised as a whole. The tiles energised from a component are its own
+/// tiles plus those of the components it leads to, which are computed once
+/// and shared by every entry that reaches them.
+#[derive(Debug)]
+pub struct SegmentGraph {
+    width: usize,
+    /// Segment of each ray that starts one
+    segments: HashMap<Ray, usize>,
+    /// Component of each segment
+    components: Vec<usize>,
+    /// Tiles energised from each component, one bit per tile
+    energized: Vec<Vec<u64>>,
+    /// Rays that each entry starts, by entry
+    entries: Vec<(Ray, Vec<Ray>)>,
+}
+
+impl SegmentGraph {
+    pub fn new(contraption: &Contraption) -> Result<Self, String> {
+        let (width, height) = (contraption.width(), contraption.height());
+        let entries = contraption
+            .entries()
+            .map(|entry| Ok((entry, contraption.start_rays(entry)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Segments reachable from any entry, with the tiles they cross
+        let mut segments = HashMap::new();
+        let mut rays = vec![];
+        let mut tiles = vec![];
+        let mut successors = vec![];
+        let mut to_visit = entries
+            .iter()
+            .flat_map(|(_, start_rays)| start_rays.iter().copied())
+            .collect::<Vec<_>>();
+        while let Some(ray) = to_visit.pop() {
+            if segments.contains_key(&ray) {
+                continue;
+            }
+            let (stop, next_rays) = contraption.follow(ray)?;
+            segments.insert(ray, rays.len());
+            rays.push(ray);
+            tiles.push(segment_tiles(ray, stop, width));
+            to_visit.extend(next_rays.iter().copied());
+            successors.push(next_rays);
+        }
+        let successors = successors
+            .iter()
+            .map(|next_rays| next_rays.iter().map(|ray| segments[ray]).collect())
+            .collect::<Vec<Vec<_>>>();
+
+        // Components come out sinks first, so the components each one leads
+        // to are already done
+        let sccs = strongly_connected_components(&successors);
+        let mut components = vec![0; rays.len()];
+        for (component, members) in sccs.iter().enumerate() {
+            for member in members {
+                components[*member] = component;
+            }
+        }
+        let words = (width * height).div_ceil(64);
+        let mut energized: Vec<Vec<u64>> = Vec::with_capacity(sccs.len());
+        for (component, members) in sccs.iter().enumerate() {
+            let mut bits = vec![0u64; words];
+            for member in members {
+                for tile in &tiles[*member] {
+                    bits[tile / 64] |= 1 << (tile % 64);
+                }
+                for next in &successors[*member] {
+                    let next = components[*next];
+                    if next != component {
+                        bits.iter_mut()
+                            .zip(&energized[next])
+                            .for_each(|(bits, next_bits)| *bits |= next_bits);
+                    }
+                }
+            }
+            energized.push(bits);
+        }
+
+        Ok(Self {
+            width,
+            segments,
+            components,
+            energized,
+            entries,
+        })
+    }
+
+    /// Tiles energised by the beam that enters at `entry`, which must be one of
+    /// the contraption's entries
+    pub fn energized_count(&self, entry: Ray) -> Result<u64, String> {
+        let (_, start_rays) = self
+            .entries
+            .iter()
+            .find(|(other, _)| *other == entry)
+            .ok_or(format!("Not an entry of the contraption. '{entry:?}'"))?;
+        Ok(self.count_from(entry, start_rays))
+    }
+
+    /// Entry that energises the most tiles, and how many it energises
+    pub fn best_entry(&self) -> Option<(Ray, u64)> {
+        self.entries
+            .iter()
+            .map(|(entry, start_rays)| (*entry, self.count_from(*entry, start_rays)))
+            .reduce(|best, other| if other.1 > best.1 { other } else { best })
+    }
+
+    /// Tiles energised from `start_rays`, plus the tile of `entry`, which is
+    /// lit even when the beam is absorbed there
+    fn count_from(&self, (x, y, _): Ray, start_rays: &[Ray]) -> u64 {
+        let sets = start_rays
+            .iter()
+            .map(|ray| &self.energized[self.components[self.segments[ray]]])
+            .collect::<Vec<_>>();
+        let entry_tile = y * self.width + x;
+        (0..self.energized.first().map_or(0, |bits| bits.len()))
+            .map(|word| {
+                let entry_bit = if entry_tile / 64 == word {
+                    1 << (entry_tile % 64)
+                } else {
+                    0
+                };
+                sets.iter()
+                    .fold(entry_bit, |bits, set| bits | set[word])
+                    .count_ones() as u64
+            })
+            .sum()
+    }
+}
+
+/// Tiles from the start of `ray` to `stop`, as `y * width + x`
+fn segment_tiles((x, y, _): Ray, stop: (usize, usize), width: usize) -> Vec<usize> {
+    if x == stop.0 {
+        (y.min(stop.1)..=y.max(stop.1))
+            .map(|y| y * width + x)
+            .collect()
+    } else {
+        (x.min(stop.0)..=x.max(stop.0))
+            .map(|x| y * width + x)
+            .collect()
+    }
+}
+
+/// Tarjan's algorithm, without recursion so long chains of segments can't
+/// overflow the stack
+///
+/// Components are listed so that every edge leads to the same component or to
+/// an earlier one.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut index = vec![usize::MAX; successors.len()];
+    let mut low_link = vec![0; successors.len()];
+    let mut on_stack = vec![false; successors.len()];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut next_index = 0;
+
+    for root in 0..successors.len() {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // Nodes being visited, with the position of the next edge to follow
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, edge)) = call_stack.last_mut() {
+            let node = *node;
+            if let Some(next) = successors[node].get(*edge).copied() {
+                *edge += 1;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some((parent, _)) = call_stack.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
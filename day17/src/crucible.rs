@@ -0,0 +1,261 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrucibleDirection {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl From<CrucibleDirection> for usize {
+    fn from(value: CrucibleDirection) -> Self {
+        match value {
+            CrucibleDirection::North => 0,
+            CrucibleDirection::South => 1,
+            CrucibleDirection::East => 2,
+            CrucibleDirection::West => 3,
+        }
+    }
+}
+
+impl CrucibleDirection {
+    /// Directions in the order of their `usize` index
+    const ALL: [CrucibleDirection; 4] = [Self::North, Self::South, Self::East, Self::West];
+
+    fn arrow(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::South => 'v',
+            Self::East => '>',
+            Self::West => '<',
+        }
+    }
+}
+
+/// Heat lost on entering each block of the city
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatLossMap {
+    blocks: Vec<Vec<u64>>,
+}
+
+/// Way from the top-left block to the bottom-right one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub heat_loss: u64,
+    /// Direction of each move of one block
+    pub moves: Vec<CrucibleDirection>,
+}
+
+/// Crucible state: position, and direction of the next straight run
+type State = (usize, usize, CrucibleDirection);
+
+impl HeatLossMap {
+    pub fn new(input: &str) -> Result<Self, String> {
+        let blocks = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| {
+                        c.to_digit(10)
+                            .map(u64::from)
+                            .ok_or(format!("Invalid heat loss '{c}'."))
+                    })
+                    .collect::<Result<Vec<_>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let width = blocks.first().map_or(0, Vec::len);
+        if width == 0 {
+            Err("Map was empty.")?;
+        }
+        if let Some(y) = blocks.iter().position(|line| line.len() != width) {
+            Err(format!("Line {} had a different length.", y + 1))?;
+        }
+        Ok(Self { blocks })
+    }
+
+    pub fn width(&self) -> usize {
+        self.blocks[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Route that loses the least heat for a crucible that moves between
+    /// `move_min` and `move_max` blocks in a straight line before turning
+    ///
+    /// A* over the crucible states, guided by the Manhattan distance to the
+    /// end times the smallest heat loss of the map, which never overestimates.
+    pub fn least_heat_loss_route(&self, move_min: usize, move_max: usize) -> Result<Route, String> {
+        let (x_max, y_max) = (self.width(), self.height());
+        let goal = (x_max - 1, y_max - 1);
+        let cheapest_block = self.blocks.iter().flatten().min().copied().unwrap_or(0);
+        let estimate = |x: usize, y: usize| ((goal.0 - x) + (goal.1 - y)) as u64 * cheapest_block;
+
+        let mut heat_lost = vec![vec![vec![u64::MAX; x_max]; y_max]; 4];
+        let mut previous = vec![vec![vec![None::<State>; x_max]; y_max]; 4];
+        let mut to_visit = BinaryHeap::new();
+        for dir in [CrucibleDirection::East, CrucibleDirection::South] {
+            heat_lost[usize::from(dir)][0][0] = 0;
+            to_visit.push(Reverse((estimate(0, 0), 0, 0, 0, usize::from(dir))));
+        }
+
+        while let Some(Reverse((_, heat_loss, x, y, dir))) = to_visit.pop() {
+            if heat_loss > heat_lost[dir][y][x] {
+                continue;
+            }
+            let dir = CrucibleDirection::ALL[dir];
+            if (x, y) == goal {
+                return Ok(Route {
+                    heat_loss,
+                    moves: reconstruct_moves(&previous, (x, y, dir)),
+                });
+            }
+
+            for (next_x, next_y) in
+                get_reacheable_tiles(x, y, dir, x_max, y_max, move_min, move_max)
+            {
+                let next_heat_loss =
+                    heat_loss + get_move_heat_loss(x, y, dir, next_x, next_y, &self.blocks)?;
+                for turn in get_possible_turns(dir) {
+                    let best = &mut heat_lost[usize::from(turn)][next_y][next_x];
+                    if next_heat_loss < *best {
+                        *best = next_heat_loss;
+                        previous[usize::from(turn)][next_y][next_x] = Some((x, y, dir));
+                        to_visit.push(Reverse((
+                            next_heat_loss + estimate(next_x, next_y),
+                            next_heat_loss,
+                            next_x,
+                            next_y,
+                            usize::from(turn),
+                        )));
+                    }
+                }
+            }
+        }
+
+        Err("Failed to calculate heat loss.".to_owned())
+    }
+
+    /// Map with the blocks entered by `route` drawn as the arrows of the moves
+    /// into them, like the puzzle
+    pub fn render(&self, route: &Route) -> String {
+        let mut lines = self
+            .blocks
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|heat_loss| char::from_digit(*heat_loss as u32, 10).unwrap_or('?'))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let (mut x, mut y) = (0, 0);
+        for dir in &route.moves {
+            match dir {
+                CrucibleDirection::North => y -= 1,
+                CrucibleDirection::South => y += 1,
+                CrucibleDirection::East => x += 1,
+                CrucibleDirection::West => x -= 1,
+            }
+            lines[y][x] = dir.arrow();
+        }
+        lines
+            .iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Moves of one block that lead to `state`, walking back through the states
+/// each run started from
+fn reconstruct_moves(previous: &[Vec<Vec<Option<State>>>], state: State) -> Vec<CrucibleDirection> {
+    let mut moves = vec![];
+    let (mut x, mut y, mut dir) = state;
+    while let Some((prev_x, prev_y, prev_dir)) = previous[usize::from(dir)][y][x] {
+        let steps = prev_x.abs_diff(x) + prev_y.abs_diff(y);
+        moves.extend(std::iter::repeat_n(prev_dir, steps));
+        (x, y, dir) = (prev_x, prev_y, prev_dir);
+    }
+    moves.reverse();
+    moves
+}
+
+fn get_reacheable_tiles(
+    x: usize,
+    y: usize,
+    dir: CrucibleDirection,
+    x_max: usize,
+    y_max: usize,
+    move_min: usize,
+    move_max: usize,
+) -> Vec<(usize, usize)> {
+    match dir {
+        CrucibleDirection::North => (move_min..=move_max)
+            .filter_map(|i| if y >= i { Some((x, y - i)) } else { None })
+            .collect(),
+        CrucibleDirection::South => (move_min..=move_max)
+            .filter_map(|i| {
+                if y + i < y_max {
+                    Some((x, y + i))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        CrucibleDirection::East => (move_min..=move_max)
+            .filter_map(|i| {
+                if x + i < x_max {
+                    Some((x + i, y))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        CrucibleDirection::West => (move_min..=move_max)
+            .filter_map(|i| if x >= i { Some((x - i, y)) } else { None })
+            .collect(),
+    }
+}
+
+fn get_possible_turns(dir: CrucibleDirection) -> [CrucibleDirection; 2] {
+    match dir {
+        CrucibleDirection::North | CrucibleDirection::South => {
+            [CrucibleDirection::East, CrucibleDirection::West]
+        }
+        CrucibleDirection::East | CrucibleDirection::West => {
+            [CrucibleDirection::North, CrucibleDirection::South]
+        }
+    }
+}
+
+fn get_move_heat_loss(
+    x: usize,
+    y: usize,
+    dir: CrucibleDirection,
+    next_x: usize,
+    next_y: usize,
+    heat_loss_map: &[Vec<u64>],
+) -> Result<u64, String> {
+    match (x, y, dir, next_x, next_y) {
+        (x, y, CrucibleDirection::North, next_x, next_y) if x == next_x && y > next_y => {
+            Ok(heat_loss_map[next_y..y].iter().map(|range| range[x]).sum())
+        }
+        (x, y, CrucibleDirection::South, next_x, next_y) if x == next_x && y < next_y => {
+            Ok(heat_loss_map[(y + 1)..=next_y]
+                .iter()
+                .map(|range| range[x])
+                .sum())
+        }
+        (x, y, CrucibleDirection::East, next_x, next_y) if x < next_x && y == next_y => {
+            Ok(heat_loss_map[y][(x + 1)..=next_x].iter().sum())
+        }
+        (x, y, CrucibleDirection::West, next_x, next_y) if x > next_x && y == next_y => {
+            Ok(heat_loss_map[y][next_x..x].iter().sum())
+        }
+        err => Err(format!(
+            "Invalid combination while calculating move heat loss. '{err:?}'"
+        )),
+    }
+}
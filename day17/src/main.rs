@@ -1,6 +1,16 @@
-use std::{collections::VecDeque, io::Read};
+mod crucible;
+
+use std::io::Read;
+
+use crucible::{HeatLossMap, Route};
+
+#[derive(Debug, Default)]
+struct Options {
+    render: bool,
+}
 
 fn main() -> Result<(), String> {
+    let options = parse_args(std::env::args().skip(1))?;
     match std::fs::File::open("inputs/day17_part1.txt") {
         Ok(mut file) => {
             let mut input = String::new();
@@ -14,6 +24,14 @@ fn main() -> Result<(), String> {
                     let part2 = path_of_least_heat_loss(&input, true);
                     println!("{:?}: {part2:?}", timer.elapsed());
 
+                    if options.render {
+                        let map = HeatLossMap::new(&input)?;
+                        for using_mega_crucible in [false, true] {
+                            let route = least_heat_loss_route(&input, using_mega_crucible)?;
+                            println!("\n{}", map.render(&route));
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => Err(err.to_string()),
@@ -23,161 +41,35 @@ fn main() -> Result<(), String> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CrucibleDirection {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl From<CrucibleDirection> for usize {
-    fn from(value: CrucibleDirection) -> Self {
-        match value {
-            CrucibleDirection::North => 0,
-            CrucibleDirection::South => 1,
-            CrucibleDirection::East => 2,
-            CrucibleDirection::West => 3,
+/// Parses `--render`, which prints the routes of both crucibles on the map
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    for arg in args {
+        match arg.as_str() {
+            "--render" => options.render = true,
+            other => return Err(format!("Unknown argument '{other}'.")),
         }
     }
+    Ok(options)
 }
 
 fn path_of_least_heat_loss(input: &str, using_mega_crucible: bool) -> Result<u64, String> {
-    let heat_loss_map = input
-        .lines()
-        .map(|line| {
-            line.as_bytes()
-                .iter()
-                .map(|c| u64::from(c - b'0'))
-                .collect::<Vec<u64>>()
-        })
-        .collect::<Vec<_>>();
-
-    let y_max = heat_loss_map.len();
-    let x_max = heat_loss_map[0].len();
-
-    let mut cum_heat_loss_map = vec![vec![vec![u64::MAX / 2; x_max]; y_max]; 4];
-    cum_heat_loss_map[0][0][0] = 0;
-    cum_heat_loss_map[1][0][0] = 0;
-    cum_heat_loss_map[2][0][0] = 0;
-    cum_heat_loss_map[3][0][0] = 0;
-
-    let mut path_testing = VecDeque::from_iter([
-        (0, 0, CrucibleDirection::East),
-        (0, 0, CrucibleDirection::South),
-    ]);
-
-    while let Some((x, y, dir)) = path_testing.pop_front() {
-        let start_pos_heat_lost = cum_heat_loss_map[usize::from(dir)][y][x];
-        let reacheable = if using_mega_crucible {
-            get_reacheable_tiles(x, y, dir, x_max, y_max, 4, 10)
-        } else {
-            get_reacheable_tiles(x, y, dir, x_max, y_max, 1, 3)
-        };
-        let turns = get_possible_turns(dir);
-
-        for (next_x, next_y) in reacheable {
-            let move_heat_loss = get_move_heat_loss(x, y, dir, next_x, next_y, &heat_loss_map)?;
-            for turn in turns {
-                if cum_heat_loss_map[usize::from(turn)][next_y][next_x]
-                    > start_pos_heat_lost + move_heat_loss
-                {
-                    cum_heat_loss_map[usize::from(turn)][next_y][next_x] =
-                        start_pos_heat_lost + move_heat_loss;
-                    path_testing.push_back((next_x, next_y, turn));
-                }
-            }
-        }
-    }
-
-    cum_heat_loss_map
-        .iter()
-        .filter_map(|end| end.last().and_then(|last_row| last_row.last()).copied())
-        .min()
-        .ok_or("Failed to calculate heat loss.".to_owned())
+    Ok(least_heat_loss_route(input, using_mega_crucible)?.heat_loss)
 }
 
-fn get_reacheable_tiles(
-    x: usize,
-    y: usize,
-    dir: CrucibleDirection,
-    x_max: usize,
-    y_max: usize,
-    move_min: usize,
-    move_max: usize,
-) -> Vec<(usize, usize)> {
-    match dir {
-        CrucibleDirection::North => (move_min..=move_max)
-            .filter_map(|i| if y >= i { Some((x, y - i)) } else { None })
-            .collect(),
-        CrucibleDirection::South => (move_min..=move_max)
-            .filter_map(|i| {
-                if y + i < y_max {
-                    Some((x, y + i))
-                } else {
-                    None
-                }
-            })
-            .collect(),
-        CrucibleDirection::East => (move_min..=move_max)
-            .filter_map(|i| {
-                if x + i < x_max {
-                    Some((x + i, y))
-                } else {
-                    None
-                }
-            })
-            .collect(),
-        CrucibleDirection::West => (move_min..=move_max)
-            .filter_map(|i| if x >= i { Some((x - i, y)) } else { None })
-            .collect(),
-    }
-}
-
-fn get_possible_turns(dir: CrucibleDirection) -> [CrucibleDirection; 2] {
-    match dir {
-        CrucibleDirection::North | CrucibleDirection::South => {
-            [CrucibleDirection::East, CrucibleDirection::West]
-        }
-        CrucibleDirection::East | CrucibleDirection::West => {
-            [CrucibleDirection::North, CrucibleDirection::South]
-        }
-    }
-}
-
-fn get_move_heat_loss(
-    x: usize,
-    y: usize,
-    dir: CrucibleDirection,
-    next_x: usize,
-    next_y: usize,
-    heat_loss_map: &[Vec<u64>],
-) -> Result<u64, String> {
-    match (x, y, dir, next_x, next_y) {
-        (x, y, CrucibleDirection::North, next_x, next_y) if x == next_x && y > next_y => {
-            Ok(heat_loss_map[next_y..y].iter().map(|range| range[x]).sum())
-        }
-        (x, y, CrucibleDirection::South, next_x, next_y) if x == next_x && y < next_y => {
-            Ok(heat_loss_map[(y + 1)..=next_y]
-                .iter()
-                .map(|range| range[x])
-                .sum())
-        }
-        (x, y, CrucibleDirection::East, next_x, next_y) if x < next_x && y == next_y => {
-            Ok(heat_loss_map[y][(x + 1)..=next_x].iter().sum())
-        }
-        (x, y, CrucibleDirection::West, next_x, next_y) if x > next_x && y == next_y => {
-            Ok(heat_loss_map[y][next_x..x].iter().sum())
-        }
-        err => Err(format!(
-            "Invalid combination while calculating move heat loss. '{err:?}'"
-        )),
+fn least_heat_loss_route(input: &str, using_mega_crucible: bool) -> Result<Route, String> {
+    let map = HeatLossMap::new(input)?;
+    if using_mega_crucible {
+        map.least_heat_loss_route(4, 10)
+    } else {
+        map.least_heat_loss_route(1, 3)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crucible::CrucibleDirection;
 
     const PART1_INPUT: &str = r"2413432311323
 3215453535623
@@ -202,4 +94,51 @@ mod tests {
     fn part2_test() {
         assert_eq!(path_of_least_heat_loss(PART1_INPUT, true), Ok(94));
     }
+
+    /// Checks that `route` reaches the bottom-right block with runs allowed
+    /// for the crucible and loses the heat it claims
+    fn check_route(input: &str, route: &Route, move_min: usize, move_max: usize) {
+        let map = input
+            .lines()
+            .map(|line| {
+                line.bytes()
+                    .map(|c| u64::from(c - b'0'))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let (mut x, mut y, mut heat_loss) = (0usize, 0usize, 0);
+        for dir in &route.moves {
+            match dir {
+                CrucibleDirection::North => y -= 1,
+                CrucibleDirection::South => y += 1,
+                CrucibleDirection::East => x += 1,
+                CrucibleDirection::West => x -= 1,
+            }
+            heat_loss += map[y][x];
+        }
+        assert_eq!((x, y), (map[0].len() - 1, map.len() - 1));
+        assert_eq!(heat_loss, route.heat_loss);
+        for run in route.moves.chunk_by(|a, b| a == b) {
+            assert!((move_min..=move_max).contains(&run.len()));
+        }
+    }
+
+    #[test]
+    fn route_test() -> Result<(), String> {
+        let route = least_heat_loss_route(PART1_INPUT, false)?;
+        assert_eq!(route.heat_loss, 102);
+        check_route(PART1_INPUT, &route, 1, 3);
+        let route = least_heat_loss_route(PART1_INPUT, true)?;
+        assert_eq!(route.heat_loss, 94);
+        check_route(PART1_INPUT, &route, 4, 10);
+
+        let map = HeatLossMap::new("1111\n9991")?;
+        let route = map.least_heat_loss_route(1, 3)?;
+        assert_eq!(route.moves.len(), 4);
+        assert_eq!(map.render(&route), "1>>>\n999v");
+
+        assert_eq!(path_of_least_heat_loss("5", false), Ok(0));
+        assert!(HeatLossMap::new("12\n3").is_err());
+        Ok(())
+    }
 }
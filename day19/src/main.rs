@@ -2,6 +2,8 @@ mod workflow;
 
 use std::io::Read;
 
+use common::blocks::{blocks, Block};
+
 use crate::workflow::{Part, Workflow, WorkflowResponse};
 
 const MIN_RATING: u64 = 1;
@@ -30,74 +32,81 @@ fn main() -> Result<(), String> {
     }
 }
 
+/// Splits the input into its workflows and parts blocks
+fn split_input<'a>(input: &'a str) -> Result<(Block<'a>, Block<'a>), String> {
+    let mut blocks = blocks(input).into_iter();
+    match (blocks.next(), blocks.next(), blocks.next()) {
+        (Some(workflows), Some(parts), None) => Ok((workflows, parts)),
+        (_, _, Some(extra)) => {
+            Err(extra.error(extra.first_line(), "Expected only workflows and parts."))
+        }
+        _ => Err("Malformed input.".to_owned()),
+    }
+}
+
+/// Parses every line of `block`, pointing errors at the line that caused them
+fn parse_block<'a, T: TryFrom<&'a str, Error = String>>(
+    block: &Block<'a>,
+) -> Result<Vec<T>, String> {
+    block
+        .numbered_lines()
+        .map(|(line_number, line)| T::try_from(line).map_err(|err| block.error(line_number, &err)))
+        .collect()
+}
+
 fn get_usable_parts(input: &str) -> Result<u64, String> {
-    if let Some((workflows, parts)) = input.split_once("\n\n") {
-        let workflows = workflows
-            .lines()
-            .map(Workflow::try_from)
-            .collect::<Result<Vec<_>, String>>()?;
-        let parts = parts
-            .lines()
-            .map(Part::try_from)
-            .collect::<Result<Vec<_>, String>>()?;
-
-        parts
-            .iter()
-            .filter_map(|part| {
-                let mut workflow_name = "in";
-                loop {
-                    match workflows
-                        .iter()
-                        .find(|workflow| workflow.name == workflow_name)
-                        .ok_or("Workflow does not exist.")
-                    {
-                        Ok(workflow) => match workflow.run_part_through_workflow(part) {
-                            Ok(WorkflowResponse::Accept) => break Some(Ok(part)),
-                            Ok(WorkflowResponse::Reject) => break None,
-                            Ok(WorkflowResponse::NextWorkflow(next)) => workflow_name = next,
-                            Err(err) => break Some(Err(err)),
-                        },
-                        Err(err) => break Some(Err(err.to_owned())),
-                    }
+    let (workflows, parts) = split_input(input)?;
+    let workflows = parse_block::<Workflow>(&workflows)?;
+    let parts = parse_block::<Part>(&parts)?;
+
+    parts
+        .iter()
+        .filter_map(|part| {
+            let mut workflow_name = "in";
+            loop {
+                match workflows
+                    .iter()
+                    .find(|workflow| workflow.name == workflow_name)
+                    .ok_or("Workflow does not exist.")
+                {
+                    Ok(workflow) => match workflow.run_part_through_workflow(part) {
+                        Ok(WorkflowResponse::Accept) => break Some(Ok(part)),
+                        Ok(WorkflowResponse::Reject) => break None,
+                        Ok(WorkflowResponse::NextWorkflow(next)) => workflow_name = next,
+                        Err(err) => break Some(Err(err)),
+                    },
+                    Err(err) => break Some(Err(err.to_owned())),
                 }
-            })
-            .map(|maybe_part| match maybe_part {
-                Ok(part) => Ok(part.value()),
-                Err(err) => Err(err),
-            })
-            .sum()
-    } else {
-        Err("Malformed input.".to_owned())
-    }
+            }
+        })
+        .map(|maybe_part| match maybe_part {
+            Ok(part) => Ok(part.value()),
+            Err(err) => Err(err),
+        })
+        .sum()
 }
 
 fn get_valid_combinations(input: &str) -> Result<u64, String> {
-    if let Some((workflows, _parts)) = input.split_once("\n\n") {
-        let workflows = workflows
-            .lines()
-            .map(Workflow::try_from)
-            .collect::<Result<Vec<_>, String>>()?;
-
-        workflows
-            .iter()
-            .find(|workflow| workflow.name == "in")
-            .map_or(
-                Err("Failed to calculate valid combinations.".to_owned()),
-                |in_workflow| {
-                    in_workflow.valid_combinations(
-                        &workflows,
-                        (
-                            MIN_RATING..=MAX_RATING,
-                            MIN_RATING..=MAX_RATING,
-                            MIN_RATING..=MAX_RATING,
-                            MIN_RATING..=MAX_RATING,
-                        ),
-                    )
-                },
-            )
-    } else {
-        Err("Malformed input.".to_owned())
-    }
+    let (workflows, _parts) = split_input(input)?;
+    let workflows = parse_block::<Workflow>(&workflows)?;
+
+    workflows
+        .iter()
+        .find(|workflow| workflow.name == "in")
+        .map_or(
+            Err("Failed to calculate valid combinations.".to_owned()),
+            |in_workflow| {
+                in_workflow.valid_combinations(
+                    &workflows,
+                    (
+                        MIN_RATING..=MAX_RATING,
+                        MIN_RATING..=MAX_RATING,
+                        MIN_RATING..=MAX_RATING,
+                        MIN_RATING..=MAX_RATING,
+                    ),
+                )
+            },
+        )
 }
 
 #[cfg(test)]
@@ -131,4 +140,19 @@ hdj{m>838:A,pv}
     fn part2_test() {
         assert_eq!(get_valid_combinations(PART1_INPUT), Ok(167_409_079_868_000));
     }
+
+    #[test]
+    fn malformed_input_test() {
+        let crlf = PART1_INPUT
+            .replace('\n', "\r\n")
+            .replace("\r\n\r\n", "\r\n \r\n\r\n");
+        assert_eq!(get_usable_parts(&crlf), Ok(19114));
+        assert_eq!(get_valid_combinations(&crlf), Ok(167_409_079_868_000));
+        assert!(get_usable_parts(&PART1_INPUT.replace("s=291", "s291"))
+            .is_err_and(|err| err.starts_with("Block 2, line 16:")));
+        assert!(
+            get_usable_parts(&format!("{PART1_INPUT}\n\n{{x=1,m=1,a=1,s=1}}"))
+                .is_err_and(|err| err.starts_with("Block 3, line 19:"))
+        );
+    }
 }
@@ -82,7 +82,10 @@ pub struct Workflow<'a> {
 }
 
 impl<'a> Workflow<'a> {
-    pub fn run_part_through_workflow(&'a self, part: &Part) -> Result<WorkflowResponse, String> {
+    pub fn run_part_through_workflow(
+        &'a self,
+        part: &Part,
+    ) -> Result<WorkflowResponse<'a>, String> {
         match self.rules.iter().find_map(|rule| {
             let Rule {
                 rating,
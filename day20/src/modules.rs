@@ -33,15 +33,9 @@ impl<'a> Module<'a> {
 
     pub fn connects_to_module(&self, module_name: &str) -> bool {
         match self {
-            Self::Broadcaster(_, connected) => {
-                connected.iter().any(|module| *module == module_name)
-            }
-            Self::FlipFlop(_, _, connected) => {
-                connected.iter().any(|module| *module == module_name)
-            }
-            Self::Conjunction(_, connected, _) => {
-                connected.iter().any(|module| *module == module_name)
-            }
+            Self::Broadcaster(_, connected) => connected.contains(&module_name),
+            Self::FlipFlop(_, _, connected) => connected.contains(&module_name),
+            Self::Conjunction(_, connected, _) => connected.contains(&module_name),
         }
     }
 
@@ -1,5 +1,7 @@
 use std::{collections::BTreeSet, io::Read};
 
+use common::extrapolation::NewtonPolynomial;
+
 fn main() -> Result<(), String> {
     match std::fs::File::open("inputs/day21_part1.txt") {
         Ok(mut file) => {
@@ -80,25 +82,6 @@ impl EdgeBuffer {
     }
 }
 
-// From Day09
-fn extrapolate_history_forward(history: &[usize]) -> Result<usize, String> {
-    let diffs = std::iter::successors(Some(history.to_vec()), |prev| {
-        if prev.iter().all(|diff| diff.eq(&0)) {
-            None
-        } else {
-            Some(prev.windows(2).map(|wind| wind[1] - wind[0]).collect())
-        }
-    })
-    .collect::<Vec<_>>();
-    diffs
-        .into_iter()
-        .rev()
-        .try_fold(0, |prev_diff, cur_diffs| match cur_diffs.last() {
-            Some(cur_diff) => Ok(prev_diff + cur_diff),
-            None => Err("A list of differences was empty.".to_owned()),
-        })
-}
-
 fn garden_plots_reachable_in_n_steps(input: &str, steps: usize) -> Result<usize, String> {
     const STEPS_TO_STEADY_STATE: usize = 4;
 
@@ -132,16 +115,15 @@ fn garden_plots_reachable_in_n_steps(input: &str, steps: usize) -> Result<usize,
             )?,
         ];
 
-        ((STEPS_TO_STEADY_STATE + 3)..widths)
-            .try_fold(width_walk, |window, _| -> Result<[usize; 4], String> {
-                Ok([
-                    window[1],
-                    window[2],
-                    window[3],
-                    extrapolate_history_forward(&window)?,
-                ])
-            })
-            .map(|window| window[3])
+        let history = width_walk
+            .into_iter()
+            .map(|plots| i64::try_from(plots).map_err(|err| err.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let remaining_widths =
+            u64::try_from(widths - (STEPS_TO_STEADY_STATE + 3)).map_err(|err| err.to_string())?;
+        NewtonPolynomial::from_history(&history)?
+            .forward(remaining_widths)
+            .and_then(|plots| usize::try_from(plots).map_err(|err| err.to_string()))
     }
 }
 
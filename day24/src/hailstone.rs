@@ -1,33 +1,16 @@
 pub trait Vec3 {
-    fn is_normal(&self) -> bool;
-    fn grided(&self) -> Self;
-
     fn cross(&self, other: &Self) -> Self;
     fn dot(&self, other: &Self) -> f64;
     fn length(&self) -> f64;
 
     fn add(&self, other: &Self) -> Self;
     fn sub(&self, other: &Self) -> Self;
-    fn mul(&self, other: &Self) -> Self;
-    fn div(&self, other: &Self) -> Self;
 
     fn mul_scalar(&self, scalar: f64) -> Self;
     fn div_scalar(&self, scalar: f64) -> Self;
 }
 
 impl Vec3 for (f64, f64, f64) {
-    fn is_normal(&self) -> bool {
-        self.0.is_normal() && self.1.is_normal() && self.2.is_normal()
-    }
-
-    fn grided(&self) -> Self {
-        (
-            self.0 - self.0.fract(),
-            self.1 - self.1.fract(),
-            self.2 - self.2.fract(),
-        )
-    }
-
     fn cross(&self, other: &Self) -> Self {
         (
             self.1 * other.2 - self.2 * other.1,
@@ -52,14 +35,6 @@ impl Vec3 for (f64, f64, f64) {
         (self.0 - other.0, self.1 - other.1, self.2 - other.2)
     }
 
-    fn mul(&self, other: &Self) -> Self {
-        (self.0 * other.0, self.1 * other.1, self.2 * other.2)
-    }
-
-    fn div(&self, other: &Self) -> Self {
-        (self.0 / other.0, self.1 / other.1, self.2 / other.2)
-    }
-
     fn mul_scalar(&self, scalar: f64) -> Self {
         (self.0 * scalar, self.1 * scalar, self.2 * scalar)
     }